@@ -0,0 +1,209 @@
+use std::{
+    error, fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::error::Error;
+
+/// Answer to a puzzle part, whatever type the solver returns.
+///
+/// Integers compare equal regardless of the variant they are stored in, so an
+/// `i32` answer matches the same value parsed back from text.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+/// A quoted answer is missing its closing quote, or has text after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnterminatedString;
+
+impl Answer {
+    /// Non-negative integers are always represented as `Unsigned`.
+    fn normalized(&self) -> Answer {
+        match *self {
+            Answer::Signed(n) if n >= 0 => Answer::Unsigned(n as u128),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.normalized(), other.normalized()) {
+            (Answer::Signed(a), Answer::Signed(b)) => a == b,
+            (Answer::Unsigned(a), Answer::Unsigned(b)) => a == b,
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl Hash for Answer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.normalized() {
+            Answer::Signed(n) => (0u8, n).hash(state),
+            Answer::Unsigned(n) => (1u8, n).hash(state),
+            Answer::Text(s) => (2u8, s).hash(state),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => n.fmt(f),
+            Answer::Unsigned(n) => n.fmt(f),
+            Answer::Text(s) => s.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Answer {
+    type Err = UnterminatedString;
+
+    /// Quoted strings, as in `answers.toml`, are always text, so that `"0123"` keeps its zero.
+    /// Bare values are integers when they can be, text otherwise.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if let Some(quoted) = s.strip_prefix('"') {
+            Answer::Text(unescape(quoted).ok_or(UnterminatedString)?)
+        } else if let Ok(n) = s.parse() {
+            Answer::Unsigned(n)
+        } else if let Ok(n) = s.parse() {
+            Answer::Signed(n)
+        } else {
+            Answer::Text(s.to_string())
+        })
+    }
+}
+
+/// Contents of a string after its opening quote, with `\` escapes resolved.
+fn unescape(quoted: &str) -> Option<String> {
+    let mut res = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return chars.as_str().is_empty().then_some(res),
+            '\\' => res.push(chars.next()?),
+            c => res.push(c),
+        }
+    }
+    None
+}
+
+impl fmt::Display for UnterminatedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unterminated string")
+    }
+}
+
+impl error::Error for UnterminatedString {}
+
+macro_rules! from_int {
+    ($variant:ident: $($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::$variant(n.into())
+            }
+        })*
+    };
+}
+
+from_int!(Unsigned: u8, u16, u32, u64, u128);
+from_int!(Signed: i8, i16, i32, i64, i128);
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Unsigned(n as u128)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(n: isize) -> Self {
+        Answer::Signed(n as i128)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+/// What a solver returns: an answer, or a `Result` for those that can fail on their input.
+pub trait IntoAnswer {
+    fn into_answer(self) -> Result<Answer, Error>;
+}
+
+impl<T> IntoAnswer for T
+where
+    Answer: From<T>,
+{
+    fn into_answer(self) -> Result<Answer, Error> {
+        Ok(Answer::from(self))
+    }
+}
+
+impl<T, E> IntoAnswer for Result<T, E>
+where
+    Answer: From<T>,
+    Error: From<E>,
+{
+    fn into_answer(self) -> Result<Answer, Error> {
+        Ok(Answer::from(self?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equality_across_widths() {
+        assert_eq!(Answer::from(42u32), Answer::from(42i64));
+        assert_eq!(Answer::from(42usize), "42".parse().unwrap());
+        assert_eq!(Answer::from(-3isize), "-3".parse().unwrap());
+        assert_ne!(Answer::from(-3i32), Answer::from(3u32));
+        assert_ne!(Answer::from(1u8), Answer::from("one"));
+    }
+
+    #[test]
+    fn round_trip() {
+        for s in [
+            "0",
+            "-17",
+            "340282366920938463463374607431768211455",
+            "ABC-12",
+        ] {
+            assert_eq!(s.parse::<Answer>().unwrap().to_string(), s);
+        }
+        assert_eq!(
+            "170141183460469231731687303715884105728"
+                .parse::<Answer>()
+                .unwrap(),
+            Answer::Unsigned(1 << 127)
+        );
+    }
+
+    #[test]
+    fn quoted_text() {
+        for s in ["0123", "-5", "42", "A\"B"] {
+            let quoted = format!("\"{}\"", s.replace('"', "\\\""));
+            assert_eq!(quoted.parse::<Answer>(), Ok(Answer::from(s)), "{quoted}");
+        }
+        assert_ne!("\"42\"".parse::<Answer>(), "42".parse::<Answer>());
+        assert_eq!("\"42".parse::<Answer>(), Err(UnterminatedString));
+        assert_eq!("\"4\"2".parse::<Answer>(), Err(UnterminatedString));
+    }
+}
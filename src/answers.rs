@@ -0,0 +1,289 @@
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::BTreeMap,
+    error, fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+use crate::{
+    answer::{Answer, UnterminatedString},
+    days::Solution,
+    error::Error,
+};
+
+pub const DEFAULT_PATH: &str = "answers.toml";
+
+/// Expected answers on the real inputs, stored as a small TOML file:
+///
+/// ```toml
+/// [day1]
+/// part1 = 142
+/// part2 = 281
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    parts: BTreeMap<(u8, u8), Answer>,
+}
+
+/// Malformed `answers.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    line: usize,
+    message: String,
+}
+
+/// Result of checking one part against its recorded answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Mismatch {
+        expected: Answer,
+        actual: Answer,
+    },
+    /// Nothing was recorded for this part yet.
+    Missing(Answer),
+    Failed(Error),
+    Panicked(String),
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self, FormatError> {
+        let mut parts = BTreeMap::new();
+        let mut day = None;
+        for (i, line) in text.lines().enumerate() {
+            let err = |message: &str| FormatError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let n = header
+                    .strip_suffix(']')
+                    .and_then(|h| h.trim().strip_prefix("day"))
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| err("expected a [dayN] header"))?;
+                day = Some(n);
+                continue;
+            }
+            let day = day.ok_or_else(|| err("answer outside of a [dayN] section"))?;
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `partN = answer`"))?;
+            let part = key
+                .trim()
+                .strip_prefix("part")
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| err("expected a partN key"))?;
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(err("missing answer"));
+            }
+            let answer = value
+                .parse()
+                .map_err(|e: UnterminatedString| err(&e.to_string()))?;
+            if parts.insert((day, part), answer).is_some() {
+                return Err(err("duplicate answer"));
+            }
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.parts.get(&(day, part))
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: Answer) {
+        self.parts.insert((day, part), answer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+thread_local! {
+    /// Panics on this thread are caught and reported by [`run_catching`].
+    static SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `solution`, turning a panic into an error message.
+pub fn run_catching(solution: &Solution, input: &str) -> Result<Result<Answer, Error>, String> {
+    // The panic hook is global, so it is only wrapped once: swapping it around each run would
+    // also silence, or miss, panics from other threads
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !SILENCED.with(Cell::get) {
+                previous(info);
+            }
+        }));
+    });
+
+    let silenced = SILENCED.with(|s| s.replace(true));
+    let res = panic::catch_unwind(AssertUnwindSafe(|| (solution.run)(input)));
+    SILENCED.with(|s| s.set(silenced));
+    res.map_err(panic_message)
+}
+
+/// Runs `solution` and compares its answer with the recorded one.
+pub fn check(solution: &Solution, input: &str, answers: &Answers) -> Outcome {
+    let actual = match run_catching(solution, input) {
+        Ok(Ok(answer)) => answer,
+        Ok(Err(e)) => return Outcome::Failed(e),
+        Err(msg) => return Outcome::Panicked(msg),
+    };
+    match answers.get(solution.day, solution.part) {
+        Some(expected) if *expected == actual => Outcome::Correct,
+        Some(expected) => Outcome::Mismatch {
+            expected: expected.clone(),
+            actual,
+        },
+        None => Outcome::Missing(actual),
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut day = None;
+        for (&(d, p), answer) in &self.parts {
+            if day != Some(d) {
+                if day.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{d}]")?;
+                day = Some(d);
+            }
+            match answer {
+                Answer::Text(s) => {
+                    let escaped = s.replace('\\', "\\\\").replace('"', "\\\"");
+                    writeln!(f, "part{p} = \"{escaped}\"")?;
+                }
+                n => writeln!(f, "part{p} = {n}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for FormatError {}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "ok"),
+            Outcome::Mismatch { expected, actual } => {
+                write!(f, "MISMATCH: expected {expected}, got {actual}")
+            }
+            Outcome::Missing(actual) => write!(f, "MISSING: no recorded answer (got {actual})"),
+            Outcome::Failed(e) => write!(f, "FAILED: {e}"),
+            Outcome::Panicked(msg) => write!(f, "PANICKED: {msg}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days;
+
+    #[test]
+    fn round_trip() {
+        let text = "[day1]\npart1 = 142\npart2 = -7\n\n[day13]\npart1 = \"A\\\"B\"\n";
+        let answers = Answers::parse(text).unwrap();
+        assert_eq!(answers.get(1, 1), Some(&Answer::from(142u32)));
+        assert_eq!(answers.get(13, 1), Some(&Answer::from("A\"B")));
+        assert_eq!(answers.to_string(), text);
+    }
+
+    #[test]
+    fn variants_round_trip() {
+        let mut answers = Answers::default();
+        let recorded = [
+            Answer::Unsigned(u128::MAX),
+            Answer::Signed(-7),
+            Answer::Text("0123".to_string()),
+            Answer::Text("-5".to_string()),
+            Answer::Text("A\\\"B".to_string()),
+        ];
+        for (i, answer) in recorded.iter().enumerate() {
+            answers.insert(i as u8 + 1, 1, answer.clone());
+        }
+        let read = Answers::parse(&answers.to_string()).unwrap();
+        for (i, answer) in recorded.iter().enumerate() {
+            let back = read.get(i as u8 + 1, 1).unwrap();
+            assert_eq!(back, answer);
+            assert_eq!(std::mem::discriminant(back), std::mem::discriminant(answer));
+        }
+    }
+
+    #[test]
+    fn format_errors() {
+        let err = Answers::parse("# comment\npart1 = 3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: answer outside of a [dayN] section"
+        );
+        let err = Answers::parse("[day1]\npart1 = 3\npart1 = 4").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(Answers::parse("[day1]\npart1 = \"x").is_err());
+    }
+
+    #[test]
+    fn outcomes() {
+        let solution = days::find(2, 1).unwrap();
+        let input = "Game 1: 3 blue, 4 red";
+        let mut answers = Answers::default();
+        assert_eq!(
+            check(solution, input, &answers),
+            Outcome::Missing(Answer::from(1u32))
+        );
+        answers.insert(2, 1, Answer::from(1u32));
+        assert_eq!(check(solution, input, &answers), Outcome::Correct);
+        answers.insert(2, 1, Answer::from(2u32));
+        assert!(matches!(
+            check(solution, input, &answers),
+            Outcome::Mismatch { .. }
+        ));
+        assert!(matches!(
+            check(solution, "Game 1: 3 blu", &answers),
+            Outcome::Failed(_)
+        ));
+        assert!(matches!(
+            check(days::find(1, 1).unwrap(), "no digits", &answers),
+            Outcome::Failed(Error::Solve(_))
+        ));
+        let panics = Solution {
+            day: 1,
+            part: 1,
+            name: "part1_panics",
+            run: |_| panic!("on purpose"),
+            timed: |_| panic!("on purpose"),
+        };
+        assert_eq!(
+            check(&panics, "", &answers),
+            Outcome::Panicked("on purpose".to_string())
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, time::Duration};
+
+use crate::{
+    days::{Solution, Timing},
+    error::Error,
+};
+
+/// Time budget for running every part once, as set by the challenge in the README.
+pub const BUDGET: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Untimed runs before measuring.
+    pub warmup: u32,
+    /// Timed runs, averaged in the report.
+    pub iterations: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            warmup: 1,
+            iterations: 5,
+        }
+    }
+}
+
+/// Average timing of a single part.
+#[derive(Debug, Clone)]
+pub struct PartTiming {
+    pub day: u8,
+    pub part: u8,
+    pub name: &'static str,
+    pub timing: Timing,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub options: Options,
+    /// Default implementation of each part, the ones counted against the budget.
+    pub parts: Vec<PartTiming>,
+    /// Other implementations, only reported.
+    pub variants: Vec<PartTiming>,
+}
+
+/// Runs `solution` on `input` with the configured warm-up and iterations.
+pub fn bench(solution: &Solution, input: &str, options: Options) -> Result<PartTiming, Error> {
+    for _ in 0..options.warmup {
+        (solution.timed)(input)?;
+    }
+    let iterations = options.iterations.max(1);
+    let mut sum = Timing::default();
+    for _ in 0..iterations {
+        let (_, timing) = (solution.timed)(input)?;
+        sum.generator += timing.generator;
+        sum.solver += timing.solver;
+    }
+    Ok(PartTiming {
+        day: solution.day,
+        part: solution.part,
+        name: solution.name,
+        timing: Timing {
+            generator: sum.generator / iterations,
+            solver: sum.solver / iterations,
+        },
+    })
+}
+
+impl Report {
+    pub fn total(&self) -> Duration {
+        self.parts.iter().map(|p| p.timing.total()).sum()
+    }
+
+    /// Parts sorted from slowest to fastest.
+    pub fn slowest(&self, n: usize) -> Vec<&PartTiming> {
+        let mut parts = self.parts.iter().collect::<Vec<_>>();
+        parts.sort_by_key(|p| std::cmp::Reverse(p.timing.total()));
+        parts.truncate(n);
+        parts
+    }
+
+    pub fn to_json(&self) -> String {
+        let list = |parts: &[PartTiming]| {
+            parts
+                .iter()
+                .map(|p| {
+                    format!(
+                        r#"{{"day":{},"part":{},"name":"{}","generator_ns":{},"solver_ns":{},"total_ns":{}}}"#,
+                        p.day,
+                        p.part,
+                        p.name,
+                        p.timing.generator.as_nanos(),
+                        p.timing.solver.as_nanos(),
+                        p.timing.total().as_nanos(),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            r#"{{"warmup":{},"iterations":{},"parts":[{}],"variants":[{}],"total_ns":{},"budget_ns":{}}}"#,
+            self.options.warmup,
+            self.options.iterations,
+            list(&self.parts),
+            list(&self.variants),
+            self.total().as_nanos(),
+            BUDGET.as_nanos(),
+        )
+    }
+}
+
+impl fmt::Display for PartTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {:02} Part {} {:<12} Generator: {:>9.3} ms    Solver: {:>9.3} ms    Total: {:>9.3} ms",
+            self.day,
+            self.part,
+            self.name,
+            millis(self.timing.generator),
+            millis(self.timing.solver),
+            millis(self.timing.total()),
+        )
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "--Durations-- ({} warm-up, {} iterations)",
+            self.options.warmup, self.options.iterations
+        )?;
+        for part in &self.parts {
+            writeln!(f, " {part}")?;
+        }
+        if !self.variants.is_empty() {
+            writeln!(f, "\n--Other variants-- (not counted in the total)")?;
+            for part in &self.variants {
+                writeln!(f, " {part}")?;
+            }
+        }
+        writeln!(f, "\n--10 slowest--")?;
+        for part in self.slowest(10) {
+            writeln!(f, " {part}")?;
+        }
+        let total = self.total();
+        write!(
+            f,
+            "\n--Result--\n Total runner duration: {:.3} ms / {} ms budget ({})",
+            millis(total),
+            BUDGET.as_millis(),
+            if total <= BUDGET { "ok" } else { "over budget" },
+        )
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let part = |day, name, ms| PartTiming {
+            day,
+            part: 1,
+            name,
+            timing: Timing {
+                generator: Duration::from_millis(1),
+                solver: Duration::from_millis(ms),
+            },
+        };
+        Report {
+            options: Options::default(),
+            parts: vec![
+                part(1, "part1", 5),
+                part(2, "part1", 900),
+                part(3, "part1", 2),
+            ],
+            variants: vec![part(2, "part1_slow", 500)],
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let report = report();
+        assert_eq!(report.total(), Duration::from_millis(910));
+        let slowest = report.slowest(2);
+        assert_eq!(slowest.iter().map(|p| p.day).collect::<Vec<_>>(), [2, 1]);
+        let text = report.to_string();
+        assert!(text.contains("(not counted in the total)\n Day 02 Part 1 part1_slow"));
+        assert!(text.ends_with("910.000 ms / 1000 ms budget (ok)"));
+    }
+
+    #[test]
+    fn json() {
+        let json = report().to_json();
+        assert!(json.starts_with(r#"{"warmup":1,"iterations":5,"parts":[{"day":1,"part":1,"name":"part1","generator_ns":1000000,"solver_ns":5000000,"total_ns":6000000},"#));
+        assert!(json.contains(r#""variants":[{"day":2,"part":1,"name":"part1_slow","#));
+        assert!(json.ends_with(r#""total_ns":910000000,"budget_ns":1000000000}"#));
+    }
+}
@@ -1,40 +1,75 @@
+use std::io::{self, Read};
+
 use itertools::Itertools;
 
-// We don't use replace over here
-#[derive(Clone, Copy, Debug)]
-struct Parser;
+use crate::util::tokens::{Parser, State, DIGITS, ENGLISH};
+
+/// Bytes read from the stream at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
 
-#[derive(Clone, Copy, Debug)]
-struct ParserState {
-    token: ParserToken,
-    index: usize,
+/// Calibration value of a line, along with the sum of those so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calibration {
+    pub value: u32,
+    pub total: u64,
 }
 
+/// Calibration values read from a stream a chunk at a time, see [`calibrations`].
 #[derive(Debug)]
-enum ParserResult {
-    MatchAlpha(u32),
-    MatchNumeric(u32),
-    Continue(ParserState),
-    Skip,
+pub struct Calibrations<'a, R> {
+    reader: R,
+    parser: &'a Parser,
+    chunk: Box<[u8]>,
+    /// Bytes of `chunk` left to go through are `pos..len`.
+    pos: usize,
+    len: usize,
+    /// Carried over chunks, so that tokens can cross their boundaries.
+    state: State,
+    /// First and last digit of the current line.
+    digits: Option<(u32, u32)>,
+    line: usize,
+    line_started: bool,
+    total: u64,
+    done: bool,
 }
 
-#[derive(Clone, Copy, Debug)]
-#[repr(u32)]
-enum ParserToken {
-    Unknown = 0,
-    One = 1,
-    Two = 2,
-    Three = 3,
-    Four = 4,
-    Five = 5,
-    Six = 6,
-    Seven = 7,
-    Eight = 8,
-    Nine = 9,
+#[aoc(day1, part1)]
+pub fn part1(input: &str) -> io::Result<u64> {
+    total(input, &Parser::new([DIGITS]))
 }
 
-#[aoc(day1, part1)]
-pub fn part1(input: &str) -> u32 {
+#[aoc(day1, part2)]
+pub fn part2(input: &str) -> io::Result<u64> {
+    total(input, &Parser::new([DIGITS, ENGLISH]))
+}
+
+fn total(input: &str, parser: &Parser) -> io::Result<u64> {
+    calibrations(input.as_bytes(), parser, CHUNK_SIZE).try_fold(0, |_, c| Ok(c?.total))
+}
+
+/// Streams one calibration value per line of `reader`, reading `chunk_size` bytes at a time.
+///
+/// A line without any digit gives an `InvalidData` error, after which the following lines are
+/// still read. Errors from `reader` end the stream.
+pub fn calibrations<R: Read>(reader: R, parser: &Parser, chunk_size: usize) -> Calibrations<'_, R> {
+    assert!(chunk_size > 0, "empty chunks");
+    Calibrations {
+        reader,
+        parser,
+        chunk: vec![0; chunk_size].into_boxed_slice(),
+        pos: 0,
+        len: 0,
+        state: State::default(),
+        digits: None,
+        line: 0,
+        line_started: false,
+        total: 0,
+        done: false,
+    }
+}
+
+#[aoc(day1, part1, lines)]
+pub fn part1_lines(input: &str) -> u32 {
     input
         .lines()
         .map(|l| {
@@ -50,8 +85,10 @@ pub fn part1(input: &str) -> u32 {
         .sum::<u32>()
 }
 
-#[aoc(day1, part2)]
-pub fn part2(input: &str) -> u32 {
+#[aoc(day1, part2, lines)]
+pub fn part2_lines(input: &str) -> u32 {
+    // We don't use replace over here
+    let parser = Parser::new([DIGITS, ENGLISH]);
     input
         .lines()
         .map(|l| {
@@ -62,7 +99,7 @@ pub fn part2(input: &str) -> u32 {
             let mut i = 0;
 
             while i < l.len() {
-                let (end, res) = Parser::parse_complete(&l[i..]);
+                let (end, res) = parser.parse_complete(&l[i..]);
 
                 if let Some(res) = res {
                     if first.is_none() {
@@ -78,116 +115,62 @@ pub fn part2(input: &str) -> u32 {
         .sum::<u32>()
 }
 
-impl Parser {
-    pub fn parse_complete(st: &[u8]) -> (usize, Option<u32>) {
-        let mut state = ParserState::new(ParserToken::Unknown, 0);
-        let mut consumed = 0;
-        while consumed < st.len() {
-            match Parser::consume(state, st[consumed]) {
-                // Alpha strings can overlap
-                ParserResult::MatchAlpha(n) => return (consumed, Some(n)),
-                // Numeric strings can't
-                ParserResult::MatchNumeric(n) => return (consumed + 1, Some(n)),
-                ParserResult::Continue(p) => state = p,
-                // We only skip on alpha strings that can overlap
-                ParserResult::Skip => return (consumed, None),
-            }
-            consumed += 1;
-        }
-        (st.len(), None)
-    }
-
-    fn consume(mut state: ParserState, ch: u8) -> ParserResult {
-        use ParserToken::*;
-
-        let ch = char::from(ch);
-        if ch.is_ascii_digit() {
-            return ParserResult::MatchNumeric(ch.to_digit(10).unwrap());
-        }
-
-        if let Unknown = state.token {
-            return ParserResult::Continue(ParserState::new(
-                match ch {
-                    'o' => One,
-                    't' => Two,
-                    'f' => Four,
-                    's' => Six,
-                    'e' => Eight,
-                    'n' => Nine,
-                    _ => Unknown,
-                },
-                1,
-            ));
-        }
-
-        let current_char = state.current_char();
-
-        if !state.next(ch) {
-            // Branching states
-            return ParserResult::Continue(match (current_char, ch) {
-                // When part of a number can be the start of another
-                ('e', 'i') => ParserState::new(Eight, 2),
-                ('n', 'i') => ParserState::new(Nine, 2),
-                ('o', 'n') => ParserState::new(One, 2),
-
-                // Branch Seven, Five, Three (from Six, Four, Two)
-                ('s', 'e') => ParserState::new(Seven, 2),
-                ('f', 'i') => ParserState::new(Five, 2),
-                ('t', 'h') => ParserState::new(Three, 2),
-                _ => return ParserResult::Skip,
-            });
-        }
-
-        if state.is_full_match() {
-            return ParserResult::MatchAlpha(state.token.result());
-        }
-
-        ParserResult::Continue(state)
+impl<R> Calibrations<'_, R> {
+    fn end_line(&mut self) -> io::Result<Calibration> {
+        self.state = State::default();
+        self.line += 1;
+        self.line_started = false;
+        let (first, last) = self.digits.take().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no digit on line {}", self.line),
+            )
+        })?;
+        let value = first * 10 + last;
+        self.total += u64::from(value);
+        Ok(Calibration {
+            value,
+            total: self.total,
+        })
     }
 }
 
-impl ParserState {
-    pub fn new(token: ParserToken, index: usize) -> Self {
-        Self { token, index }
-    }
-
-    pub fn next(&mut self, next_char: char) -> bool {
-        if char::from(self.token.pattern()[self.index]) != next_char {
-            return false;
-        }
-        self.index += 1;
-        true
-    }
-
-    pub fn is_full_match(&self) -> bool {
-        self.index >= self.token.pattern().len()
-    }
-
-    fn current_char(&self) -> char {
-        char::from(self.token.pattern()[self.index - 1])
-    }
-}
+impl<R: Read> Iterator for Calibrations<'_, R> {
+    type Item = io::Result<Calibration>;
 
-impl ParserToken {
-    const fn result(self) -> u32 {
-        self as u32
-    }
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.pos == self.len {
+                if self.done {
+                    return None;
+                }
+                match self.reader.read(&mut self.chunk) {
+                    Ok(0) => {
+                        // The last line may or may not end with a newline
+                        self.done = true;
+                        return self.line_started.then(|| self.end_line());
+                    }
+                    Ok(len) => (self.pos, self.len) = (0, len),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                continue;
+            }
 
-    const fn pattern(self) -> &'static [u8] {
-        use ParserToken::*;
-        match self {
-            Unknown => "",
-            One => "one",
-            Two => "two",
-            Three => "three",
-            Four => "four",
-            Five => "five",
-            Six => "six",
-            Seven => "seven",
-            Eight => "eight",
-            Nine => "nine",
+            let c = self.chunk[self.pos];
+            self.pos += 1;
+            if c == b'\n' {
+                return Some(self.end_line());
+            }
+            self.line_started = true;
+            if let Some(n) = self.parser.feed(&mut self.state, c) {
+                let first = self.digits.map_or(n, |(first, _)| first);
+                self.digits = Some((first, n));
+            }
         }
-        .as_bytes()
     }
 }
 
@@ -195,33 +178,74 @@ impl ParserToken {
 mod tests {
     use super::*;
 
-    #[test]
-    fn part1_example() {
-        let input = r#"1abc2
-pqr3stu8vwx
-a1b2c3d4e5f
-treb7uchet"#;
-        assert_eq!(part1(input), 142);
+    /// Lines of random words, digits and noise, each with at least one digit.
+    fn generate(lines: usize) -> String {
+        const PIECES: [&str; 16] = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ei", "ght",
+            "n", "e", "xyz", "oneight", "\r",
+        ];
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut rand = |n: usize| {
+            // xorshift
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize % n
+        };
+        let mut res = String::new();
+        for _ in 0..lines {
+            let digit_at = rand(6);
+            for i in 0..6 {
+                if i == digit_at {
+                    res.push(char::from(b'0' + rand(10) as u8));
+                }
+                res.push_str(PIECES[rand(PIECES.len())]);
+            }
+            res.push('\n');
+        }
+        res
     }
 
     #[test]
-    fn part2_example() {
-        let input = r#"two1nine
-eightwothree
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen"#;
-        assert_eq!(part2(input), 281);
+    fn streaming_matches_lines() {
+        let input = generate(2000);
+        let parser = Parser::new([DIGITS, ENGLISH]);
+        for chunk_size in [1, 2, 3, 7, 64, CHUNK_SIZE] {
+            let total = calibrations(input.as_bytes(), &parser, chunk_size)
+                .map(|c| c.unwrap().total)
+                .last();
+            let expected = u64::from(part2_lines(&input));
+            assert_eq!(total, Some(expected), "chunks of {chunk_size}");
+        }
+        assert_eq!(part1(&input).unwrap(), u64::from(part1_lines(&input)));
+        assert_eq!(part2(&input).unwrap(), u64::from(part2_lines(&input)));
+        assert_eq!(
+            part1("1\nnone\n2").unwrap_err().to_string(),
+            "no digit on line 2"
+        );
     }
 
     #[test]
-    fn part2_edge() {
-        assert_eq!(part2("xgkfonethreexnlcptbgxhnine4fivetwosix"), 16);
-        assert_eq!(part2("sevenine"), 79);
-        assert_eq!(part2("oneight"), 18);
-        assert_eq!(part2("threeeeight"), 38);
-        assert_eq!(part2("ninine2"), 92);
+    fn per_line() {
+        let parser = Parser::new([DIGITS, ENGLISH]);
+        let values = calibrations("eigh\ntwo1\n\nsevenine".as_bytes(), &parser, 3)
+            .map(|c| c.map_err(|e| e.to_string()))
+            .collect_vec();
+        assert_eq!(
+            values,
+            [
+                Err("no digit on line 1".to_string()),
+                Ok(Calibration {
+                    value: 21,
+                    total: 21
+                }),
+                Err("no digit on line 3".to_string()),
+                Ok(Calibration {
+                    value: 79,
+                    total: 100
+                }),
+            ]
+        );
+        assert_eq!(calibrations("".as_bytes(), &parser, 3).count(), 0);
     }
 }
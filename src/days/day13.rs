@@ -1,4 +1,9 @@
-use itertools::Itertools;
+use std::str::FromStr;
+
+use crate::{
+    error::{parse_all, ParseError, Source},
+    util::geom::Vec2,
+};
 
 #[derive(Debug, Clone)]
 pub struct Pattern {
@@ -7,27 +12,8 @@ pub struct Pattern {
 }
 
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Vec<Pattern> {
-    input
-        .split("\n\n")
-        .map(|block| {
-            let mut rows = vec![];
-            let mut columns = vec![vec![]; block.lines().next().unwrap().len()];
-
-            for (y, line) in block.lines().enumerate() {
-                let mut row = vec![];
-                for (x, ch) in line.chars().enumerate() {
-                    if ch == '#' {
-                        row.push(x);
-                        columns[x].push(y);
-                    }
-                }
-                rows.push(row);
-            }
-
-            Pattern { rows, columns }
-        })
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Vec<Pattern>, ParseError> {
+    parse_all(input, input.split("\n\n"))
 }
 
 #[aoc(day13, part1)]
@@ -112,6 +98,25 @@ fn find_reflection(lines: &[Vec<usize>], skip: Option<usize>) -> Option<usize> {
     match_idx.map(|_| match_i)
 }
 
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Source::new(13, s).grid("'#' or '.'", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+        let mut rows = vec![vec![]; grid.height()];
+        let mut columns = vec![vec![]; grid.width()];
+        for (Vec2 { x, y }, _) in grid.iter().filter(|(_, rock)| **rock) {
+            rows[y].push(x);
+            columns[x].push(y);
+        }
+        Ok(Pattern { rows, columns })
+    }
+}
+
 impl Pattern {
     pub fn get(&self, row: usize, column: usize) -> bool {
         self.rows[row].binary_search(&column).is_ok()
@@ -132,48 +137,3 @@ impl Pattern {
         }
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"#.##..##.
-..#.##.#.
-##......#
-##......#
-..#.##.#.
-..##..##.
-#.#.##.#.
-
-#...##..#
-#....#..#
-..##..###
-#####.##.
-#####.##.
-..##..###
-#....#..#"#;
-        assert_eq!(part1(&parse(&input)), 405);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"#.##..##.
-..#.##.#.
-##......#
-##......#
-..#.##.#.
-..##..##.
-#.#.##.#.
-
-#...##..#
-#....#..#
-..##..###
-#####.##.
-#####.##.
-..##..###
-#....#..#"#;
-        assert_eq!(part2(&parse(&input)), 400);
-    }
-}
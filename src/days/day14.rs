@@ -1,117 +1,68 @@
-use fxhash::FxHashMap;
+use std::str::FromStr;
+
 use itertools::Itertools;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Grid(Vec<Vec<Tile>>);
+use crate::{
+    error::{ParseError, Source},
+    util::{cycle, dir::Direction, grid::Grid},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Tile {
+pub enum Tile {
     Rock,
     Empty,
     Obstacle,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum Direction {
-    North,
-    West,
-    South,
-    East,
-}
-
 #[aoc_generator(day14)]
-fn parse(input: &str) -> Grid {
-    Grid(
-        input
-            .lines()
-            .map(|l| {
-                l.chars()
-                    .map(|c| match c {
-                        '.' => Tile::Empty,
-                        'O' => Tile::Rock,
-                        '#' => Tile::Obstacle,
-                        c => panic!("unknown {c:?}"),
-                    })
-                    .collect_vec()
-            })
-            .collect_vec(),
-    )
+pub(super) fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+    input.parse()
 }
 
 #[aoc(day14, part1)]
-pub fn part1(input: &Grid) -> usize {
+pub fn part1(input: &Grid<Tile>) -> usize {
     let mut input = input.clone();
-    input.move_dir(Direction::North, None);
-    let max_y = input.0.len();
-    input
-        .0
-        .into_iter()
-        .enumerate()
-        .flat_map(|(y, l)| l.into_iter().map(move |c| (y, c)))
-        .filter(|(_, c)| *c == Tile::Rock)
-        .map(|(y, _)| max_y - y)
-        .sum()
+    input.move_dir(Direction::Up);
+    input.north_load()
 }
 
 #[aoc(day14, part2)]
-pub fn part2(input: &Grid) -> usize {
-    const TARGET: usize = 1_000_000_000 * 4;
-
-    let mut input = input.clone();
-    let mut memo = FxHashMap::default();
-    let mut i = 0;
-    while i < TARGET {
-        let dir = [
-            Direction::North,
-            Direction::West,
-            Direction::South,
-            Direction::East,
-        ][i % 4];
-        let res = input.move_dir(dir, Some((&mut memo, i)));
-        if let Some((cycle, j)) = res {
-            input = cycle;
-            let cycle_len = i - j;
-            i += (TARGET - i) / cycle_len * cycle_len;
+pub fn part2(input: &Grid<Tile>) -> usize {
+    let spin = |mut grid: Grid<Tile>| {
+        for dir in [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+        ] {
+            grid.move_dir(dir);
         }
-        i += 1;
-    }
-    let max_y = input.0.len();
-    input
-        .0
-        .into_iter()
-        .enumerate()
-        .flat_map(|(y, l)| l.into_iter().map(move |c| (y, c)))
-        .filter(|(_, c)| *c == Tile::Rock)
-        .map(|(y, _)| max_y - y)
-        .sum()
+        grid
+    };
+    cycle::nth_state(input.clone(), spin, 1_000_000_000).north_load()
 }
 
-impl Grid {
-    pub fn move_dir(
-        &mut self,
-        dir: Direction,
-        memo: Option<(&mut FxHashMap<(Grid, Direction), (Grid, usize)>, usize)>,
-    ) -> Option<(Grid, usize)> {
-        let mut key = None;
-        if let Some((memo, _)) = &memo {
-            let k = (self.clone(), dir);
-            if let Some(g) = memo.get(&k) {
-                self.clone_from(&g.0);
-                return Some(g.clone());
-            }
-            key = Some(k);
-        }
+impl Grid<Tile> {
+    pub fn north_load(&self) -> usize {
+        let max_y = self.height();
+        self.iter()
+            .filter(|(_, c)| **c == Tile::Rock)
+            .map(|(pos, _)| max_y - pos.y)
+            .sum()
+    }
+
+    pub fn move_dir(&mut self, dir: Direction) {
         match dir {
             // Iterator types and logic differ between direction, hard to extract
-            Direction::North => {
-                'coords: for (y, x) in (0..self.0.len()).cartesian_product(0..self.0[0].len()) {
-                    let c = self.0[y][x];
-                    if c == Tile::Rock && y > 0 && self.0[y - 1][x] == Tile::Empty {
+            Direction::Up => {
+                'coords: for (y, x) in (0..self.height()).cartesian_product(0..self.width()) {
+                    let c = self[(x, y)];
+                    if c == Tile::Rock && y > 0 && self[(x, y - 1)] == Tile::Empty {
                         let mut last_y = y;
                         for y in (0..y).rev() {
-                            if self.0[y][x] == Tile::Empty {
-                                self.0[y][x] = c;
-                                self.0[last_y][x] = Tile::Empty;
+                            if self[(x, y)] == Tile::Empty {
+                                self[(x, y)] = c;
+                                self[(x, last_y)] = Tile::Empty;
                                 last_y = y;
                             } else {
                                 continue 'coords;
@@ -119,20 +70,19 @@ impl Grid {
                         }
                     }
                 }
-                return None;
             }
-            Direction::South => {
-                'coords: for (y, x) in (0..self.0.len() - 1)
+            Direction::Down => {
+                'coords: for (y, x) in (0..self.height() - 1)
                     .rev()
-                    .cartesian_product(0..self.0[0].len())
+                    .cartesian_product(0..self.width())
                 {
-                    let c = self.0[y][x];
-                    if c == Tile::Rock && self.0[y + 1][x] == Tile::Empty {
+                    let c = self[(x, y)];
+                    if c == Tile::Rock && self[(x, y + 1)] == Tile::Empty {
                         let mut last_y = y;
-                        for y in y + 1..self.0.len() {
-                            if self.0[y][x] == Tile::Empty {
-                                self.0[y][x] = c;
-                                self.0[last_y][x] = Tile::Empty;
+                        for y in y + 1..self.height() {
+                            if self[(x, y)] == Tile::Empty {
+                                self[(x, y)] = c;
+                                self[(x, last_y)] = Tile::Empty;
                                 last_y = y;
                             } else {
                                 continue 'coords;
@@ -141,17 +91,17 @@ impl Grid {
                     }
                 }
             }
-            Direction::East => {
+            Direction::Right => {
                 'coords: for (x, y) in
-                    ((0..self.0[0].len() - 1).rev()).cartesian_product(0..self.0.len())
+                    ((0..self.width() - 1).rev()).cartesian_product(0..self.height())
                 {
-                    let c = self.0[y][x];
-                    if c == Tile::Rock && self.0[y][x + 1] == Tile::Empty {
+                    let c = self[(x, y)];
+                    if c == Tile::Rock && self[(x + 1, y)] == Tile::Empty {
                         let mut last_x = x;
-                        for x in x + 1..self.0[0].len() {
-                            if self.0[y][x] == Tile::Empty {
-                                self.0[y][x] = c;
-                                self.0[y][last_x] = Tile::Empty;
+                        for x in x + 1..self.width() {
+                            if self[(x, y)] == Tile::Empty {
+                                self[(x, y)] = c;
+                                self[(last_x, y)] = Tile::Empty;
                                 last_x = x;
                             } else {
                                 continue 'coords;
@@ -160,15 +110,15 @@ impl Grid {
                     }
                 }
             }
-            Direction::West => {
-                'coords: for (y, x) in (0..self.0.len()).cartesian_product(0..self.0[0].len()) {
-                    let c = self.0[y][x];
-                    if c == Tile::Rock && x > 0 && self.0[y][x - 1] == Tile::Empty {
+            Direction::Left => {
+                'coords: for (y, x) in (0..self.height()).cartesian_product(0..self.width()) {
+                    let c = self[(x, y)];
+                    if c == Tile::Rock && x > 0 && self[(x - 1, y)] == Tile::Empty {
                         let mut last_x = x;
                         for x in (0..x).rev() {
-                            if self.0[y][x] == Tile::Empty {
-                                self.0[y][x] = c;
-                                self.0[y][last_x] = Tile::Empty;
+                            if self[(x, y)] == Tile::Empty {
+                                self[(x, y)] = c;
+                                self[(last_x, y)] = Tile::Empty;
                                 last_x = x;
                             } else {
                                 continue 'coords;
@@ -178,46 +128,20 @@ impl Grid {
                 }
             }
         }
-
-        if let Some(key) = key {
-            let (memo, i) = memo.unwrap();
-            memo.insert(key, (self.clone(), i));
-        }
-        return None;
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"O....#....
-O.OO#....#
-.....##...
-OO.#O....O
-.O.....O#.
-O.#..O.#.#
-..O..#O..O
-.......O..
-#....###..
-#OO..#...."#;
-        assert_eq!(part1(&parse(&input)), 136);
-    }
+impl FromStr for Grid<Tile> {
+    type Err = ParseError;
 
-    #[test]
-    fn part2_example() {
-        let input = r#"O....#....
-O.OO#....#
-.....##...
-OO.#O....O
-.O.....O#.
-O.#..O.#.#
-..O..#O..O
-.......O..
-#....###..
-#OO..#...."#;
-        assert_eq!(part2(&parse(&input)), 64);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::new(14, s).grid("'.', 'O' or '#'", |c| {
+            Some(match c {
+                '.' => Tile::Empty,
+                'O' => Tile::Rock,
+                '#' => Tile::Obstacle,
+                _ => return None,
+            })
+        })
     }
 }
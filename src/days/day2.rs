@@ -1,93 +1,182 @@
-use itertools::Itertools;
+use std::str::FromStr;
 
+use crate::{
+    error::{ParseError, Source},
+    util::graph::Interner,
+};
+
+/// Every game, with the colours they mention interned in order of appearance.
+#[derive(Debug)]
+pub struct Record {
+    colors: Interner,
+    games: Vec<Game>,
+}
+
+#[derive(Debug)]
 pub struct Game {
     id: i32,
-    turns: Vec<Vec<Cube>>,
+    turns: Vec<Bag>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
-struct Cube {
-    ty: CubeType,
-    amount: u32,
-}
+/// Cube counts indexed by colour id, missing colours having none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bag(Vec<u32>);
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
-enum CubeType {
-    Red,
-    Blue,
-    Green,
-}
+/// Colours of the puzzle's own games, the generator rejects any other.
+const COLORS: [&str; 3] = ["red", "green", "blue"];
 
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<Game> {
-    input
-        .lines()
-        .map(|l| {
-            let mut parts = l.split(": ");
-            let id = parts.next().unwrap();
-            let turns = parts.next().unwrap();
-
-            let id = id.strip_prefix("Game ").unwrap().parse().unwrap();
-
-            let turns = turns
-                .split("; ")
-                .map(|turn| {
-                    turn.split(", ")
-                        .map(|cube| {
-                            let mut parts = cube.split(" ");
-                            let amount = parts.next().unwrap().parse().unwrap();
-                            let ty = parts.next().unwrap();
-                            let ty = match ty {
-                                "red" => CubeType::Red,
-                                "blue" => CubeType::Blue,
-                                "green" => CubeType::Green,
-                                t => panic!("invalid type {t}"),
-                            };
-                            Cube { ty, amount }
-                        })
-                        .collect()
-                })
-                .collect();
-
-            Game { id, turns }
-        })
-        .collect()
+pub(super) fn parse(input: &str) -> Result<Record, ParseError> {
+    Record::with_colors(input, &COLORS)
 }
 
 #[aoc(day2, part1)]
-pub fn part1(input: &[Game]) -> i32 {
-    use CubeType::*;
-
+pub fn part1(input: &Record) -> i32 {
+    let bag = input.bag([("red", 12), ("green", 13), ("blue", 14)]);
     input
+        .games
         .iter()
-        .filter(|g| {
-            g.turns.iter().all(|t| {
-                !t.iter().any(|c| {
-                    c.ty == Red && c.amount > 12
-                        || c.ty == Green && c.amount > 13
-                        || c.ty == Blue && c.amount > 14
-                })
-            })
-        })
+        .filter(|g| g.is_possible(&bag))
         .map(|g| g.id)
         .sum()
 }
 
 #[aoc(day2, part2)]
-pub fn part2(input: &[Game]) -> u32 {
-    input
-        .iter()
-        .map(|g| {
-            let mut flattened = g.turns.iter().flatten().collect_vec();
-            flattened.sort_unstable_by_key(|c| c.ty);
-            flattened
-                .into_iter()
-                .group_by(|c| c.ty)
-                .into_iter()
-                .map(|(_, c)| c.map(|c| c.amount).max().unwrap())
-                .product::<u32>()
+pub fn part2(input: &Record) -> u32 {
+    input.games().iter().map(|g| g.min_bag().power()).sum()
+}
+
+impl Record {
+    /// Parses games drawing only from `colors`; [`Record::from_str`] accepts any alphabetic name.
+    pub fn with_colors(s: &str, colors: &[&str]) -> Result<Self, ParseError> {
+        Self::parse_with(s, |color| colors.contains(&color))
+    }
+
+    fn parse_with(s: &str, is_color: impl Fn(&str) -> bool) -> Result<Self, ParseError> {
+        let src = Source::new(2, s);
+        let mut colors = Interner::new();
+        let games = src
+            .lines()
+            .map(|l| {
+                let (id, turns) = src.split_once(l, ": ")?;
+                let id = src.parse(src.strip_prefix(id, "Game ")?, "game id")?;
+
+                let turns = turns
+                    .split("; ")
+                    .map(|turn| {
+                        let mut bag = Bag::default();
+                        for cube in turn.split(", ") {
+                            let (amount, color) = src.split_once(cube, " ")?;
+                            let amount = src.parse(amount, "cube count")?;
+                            if !is_color(color) {
+                                return Err(src.error(color, "cube color"));
+                            }
+                            // Each cube is checked on its own, so a repeated colour counts
+                            // as its largest draw
+                            let color = colors.intern(color);
+                            bag.set(color, bag.get(color).max(amount));
+                        }
+                        Ok(bag)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Game { id, turns })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Record { colors, games })
+    }
+
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    /// Bag with the given counts; colours no game mentions can't matter and are left out.
+    pub fn bag<'a>(&self, counts: impl IntoIterator<Item = (&'a str, u32)>) -> Bag {
+        let mut bag = Bag::default();
+        for (name, amount) in counts {
+            if let Some(color) = self.colors.get(name) {
+                bag.set(color, amount);
+            }
+        }
+        bag
+    }
+
+    /// Smallest single bag that makes every game possible.
+    pub fn min_bag(&self) -> Bag {
+        self.games
+            .iter()
+            .fold(Bag::default(), |acc, g| acc.union(&g.min_bag()))
+    }
+
+    pub fn color(&self, name: &str) -> Option<u32> {
+        self.colors.get(name)
+    }
+
+    pub fn color_name(&self, color: u32) -> &str {
+        self.colors.name(color)
+    }
+}
+
+impl Game {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn turns(&self) -> &[Bag] {
+        &self.turns
+    }
+
+    /// Every turn could have been drawn from `bag`.
+    pub fn is_possible(&self, bag: &Bag) -> bool {
+        self.turns.iter().all(|t| bag.contains(t))
+    }
+
+    /// Fewest cubes of each colour that make the game possible.
+    pub fn min_bag(&self) -> Bag {
+        self.turns
+            .iter()
+            .fold(Bag::default(), |acc, t| acc.union(t))
+    }
+}
+
+impl Bag {
+    pub fn get(&self, color: u32) -> u32 {
+        self.0.get(color as usize).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, color: u32, amount: u32) {
+        let color = color as usize;
+        if color >= self.0.len() {
+            self.0.resize(color + 1, 0);
+        }
+        self.0[color] = amount;
+    }
+
+    /// There are at least as many cubes of each colour as in `other`.
+    pub fn contains(&self, other: &Bag) -> bool {
+        (0..other.0.len() as u32).all(|c| other.get(c) <= self.get(c))
+    }
+
+    /// The most cubes of each colour from either bag.
+    pub fn union(&self, other: &Bag) -> Bag {
+        let len = self.0.len().max(other.0.len()) as u32;
+        Bag((0..len).map(|c| self.get(c).max(other.get(c))).collect())
+    }
+
+    /// Product of the counts, leaving out colours without any cube.
+    pub fn power(&self) -> u32 {
+        self.0.iter().filter(|&&n| n != 0).product()
+    }
+}
+
+impl FromStr for Record {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, |color| {
+            !color.is_empty() && color.chars().all(char::is_alphabetic)
         })
-        .sum()
+    }
 }
 
 #[cfg(test)]
@@ -95,22 +184,48 @@ mod tests {
     use super::*;
 
     #[test]
-    fn part1_example() {
-        let input = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"#;
-        assert_eq!(part1(&parse(&input)), 8);
+    fn parse_error() {
+        let err = parse("Game 1: 3 blue\nGame 2: 4 purple").unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 11));
+        assert_eq!(err.found(), "purple");
+
+        // Any colour goes outside the puzzle
+        assert!("Game 1: 4 Purple, 1 vermelho".parse::<Record>().is_ok());
+        let err = "Game 1: 4 blue-ish".parse::<Record>().unwrap_err();
+        assert_eq!(err.found(), "blue-ish");
     }
 
     #[test]
-    fn part2_example() {
-        let input = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"#;
-        assert_eq!(part2(&parse(&input)), 2286);
+    fn repeated_color() {
+        let input = parse("Game 1: 13 red, 5 red; 2 blue, 1 blue").unwrap();
+        let game = &input.games()[0];
+        let red = input.color("red").unwrap();
+        assert_eq!(game.turns()[0].get(red), 13);
+        assert_eq!(part1(&input), 0);
+        assert_eq!(part2(&input), 26);
+    }
+
+    #[test]
+    fn bags() {
+        let input: Record = "Game 1: 3 blue, 4 red; 2 yellow\n\
+                             Game 2: 1 red, 5 yellow; 2 blue\n\
+                             Game 7: 9 blue"
+            .parse()
+            .unwrap();
+        let yellow = input.color("yellow").unwrap();
+        assert_eq!(input.color_name(yellow), "yellow");
+        assert_eq!(input.color("green"), None);
+
+        let min = input.games()[1].min_bag();
+        assert_eq!((min.get(yellow), min.power()), (5, 10));
+        let bag = input.bag([("red", 4), ("blue", 3), ("yellow", 5), ("green", 1)]);
+        let possible = input.games().iter().map(|g| g.is_possible(&bag));
+        assert!(possible.eq([true, true, false]));
+        assert_eq!(input.games()[2].id(), 7);
+
+        let all = input.min_bag();
+        assert_eq!(all, input.bag([("blue", 9), ("red", 4), ("yellow", 5)]));
+        assert!(input.games().iter().all(|g| g.is_possible(&all)));
+        assert!(!input.games()[2].is_possible(&input.games()[0].min_bag()));
     }
 }
@@ -1,102 +1,48 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, str::FromStr};
 
 use fxhash::{FxHashMap, FxHashSet};
 use itertools::Itertools;
 
-use super::day8::lcm;
+use crate::{
+    error::{ParseError, Source},
+    util::{
+        bit_set::DynBitSet,
+        cycle::{self, Cycle, Schedule},
+        graph::{self, AdjList, Graph, Interner},
+    },
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Sheet {
-    modules: Vec<Module>,
+    names: Interner,
+    modules: Vec<ModuleType>,
+    outputs: AdjList,
     low_count: usize,
     high_count: usize,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Module {
-    name: String,
-    ty: ModuleType,
-    outputs: Vec<usize>,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 enum ModuleType {
     FlipFlop(bool),
-    Conjunction(FxHashMap<String, bool>),
+    /// Inputs that last sent a high pulse, and the total number of inputs.
+    Conjunction(DynBitSet, usize),
     Broadcast,
+    /// Only referenced as an output, like `rx`.
+    Untyped,
 }
 
 #[aoc_generator(day20)]
-fn parse(input: &str) -> Sheet {
-    let mut modules = input
-        .lines()
-        .map(|l| {
-            let ty = match l.chars().next().unwrap() {
-                '%' => ModuleType::FlipFlop(false),
-                '&' => ModuleType::Conjunction(FxHashMap::default()),
-                _ => ModuleType::Broadcast,
-            };
-            let (name, outputs) = l.split(" -> ").collect_tuple().unwrap();
-            let name = if let ModuleType::Broadcast = ty {
-                name.to_string()
-            } else {
-                name[1..].to_string()
-            };
-            let outputs = outputs.split(", ").map(|s| s.to_string()).collect_vec();
-
-            (
-                Module {
-                    ty,
-                    name,
-                    outputs: vec![],
-                },
-                outputs,
-            )
-        })
-        .collect_vec();
-    let mut res: Vec<Module> = vec![];
-    let mod_clone = modules.clone();
-    for i in 0..modules.len() {
-        let name = modules[i].0.name.clone();
-        if let ModuleType::Conjunction(c) = &mut modules[i].0.ty {
-            for module in &mod_clone {
-                if module.1.contains(&name) {
-                    c.insert(module.0.name.clone(), false);
-                }
-            }
-        }
-        let outputs = modules[i]
-            .1
-            .iter()
-            .map(|s| {
-                modules
-                    .iter()
-                    .position(|m| &m.0.name == s)
-                    .unwrap_or(usize::MAX)
-            })
-            .collect_vec();
-        let (module, _) = &mut modules[i];
-        module.outputs = outputs;
-        res.push(module.clone());
-    }
-    Sheet {
-        modules: res,
-        low_count: 0,
-        high_count: 0,
-    }
+pub(super) fn parse(input: &str) -> Result<Sheet, ParseError> {
+    input.parse()
 }
 
 #[aoc(day20, part1)]
 pub fn part1(input: &Sheet) -> usize {
     let mut state = input.clone();
-    let broadcaster = state
-        .modules
-        .iter()
-        .position(|m| matches!(m.ty, ModuleType::Broadcast))
-        .unwrap();
+    let broadcaster = state.broadcaster();
     let mut res = (0, 0);
     for _ in 0..1000 {
-        let (nl, nh) = state.send(0, broadcaster, false);
+        let (nl, nh) = state.send(broadcaster, broadcaster, false);
         res.0 += nl;
         res.1 += nh;
     }
@@ -112,41 +58,183 @@ pub fn part2(input: &Sheet) -> usize {
     // Assume rx is the output of a & module, the high-intervals of each
     // dependency seem to have a fixed cycle.
 
-    let first_dep = input
-        .modules
-        .iter()
-        .position(|m| m.outputs.contains(&usize::MAX))
-        .unwrap();
-    let mut deps: FxHashSet<usize> = input
-        .modules
-        .iter()
-        .enumerate()
-        .filter(|(_, m)| m.outputs.contains(&first_dep))
-        .map(|(i, _)| i)
-        .collect();
-    let broadcaster = input
-        .modules
+    // Rather than trusting those cycles, detect them: each dependency is driven by its own
+    // sub-circuit, whose states can be told apart from the rest. All of them are tracked along
+    // a single run of presses, keeping only the bits of their own modules.
+    let inputs = input.outputs.reversed();
+    let rx = input.names.get("rx").unwrap();
+    let first_dep = inputs.neighbors(rx)[0];
+    let deps: FxHashSet<u32> = inputs.neighbors(first_dep).iter().copied().collect();
+    let broadcaster = input.broadcaster();
+
+    let mut trackers = deps
         .iter()
-        .position(|m| matches!(m.ty, ModuleType::Broadcast))
-        .unwrap();
-    let mut cycles = vec![];
+        .map(|&dep| Tracker {
+            dep,
+            circuit: input.state_bits(graph::dfs(&inputs, dep), &inputs),
+            seen: FxHashMap::default(),
+            hits: vec![],
+            cycle: None,
+        })
+        .collect_vec();
     let mut state = input.clone();
+    for i in 0.. {
+        let mut done = true;
+        for tracker in trackers.iter_mut().filter(|t| t.cycle.is_none()) {
+            let key = state.circuit_state(&tracker.circuit);
+            if let Some(mu) = tracker.seen.insert(key, i) {
+                tracker.cycle = Some(Cycle { mu, lambda: i - mu });
+            } else {
+                done = false;
+            }
+        }
+        if done {
+            break;
+        }
+
+        let found = state.send_2(broadcaster, broadcaster, false, first_dep, &deps);
+        for tracker in trackers.iter_mut().filter(|t| t.cycle.is_none()) {
+            if found.contains(&tracker.dep) {
+                tracker.hits.push(i);
+            }
+        }
+    }
+
+    let schedules = trackers
+        .into_iter()
+        .map(|t| Schedule {
+            cycle: t.cycle.unwrap(),
+            hits: t.hits,
+        })
+        .collect_vec();
+    // Hits are indexed by the state before the press
+    cycle::first_common_hit(&schedules).expect("rx never receives a low pulse") + 1
+}
+
+/// Cycle detection for the sub-circuit driving one dependency of `rx`'s conjunction.
+struct Tracker {
+    dep: u32,
+    /// Bits of state of the modules upstream of `dep`.
+    circuit: Vec<(u32, u32)>,
+    /// Index of the first press at which each state of the circuit was seen.
+    seen: FxHashMap<Vec<u64>, usize>,
+    /// Presses at which `dep` sent a high pulse, up to the first repeated state.
+    hits: Vec<usize>,
+    cycle: Option<Cycle>,
+}
+
+impl FromStr for Sheet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(20, s);
+        let mut names = Interner::new();
+        let mut modules = vec![];
+        let mut outputs = AdjList::default();
+        for l in s.lines() {
+            let ty = match l.chars().next().unwrap_or_default() {
+                '%' => ModuleType::FlipFlop(false),
+                '&' => ModuleType::Conjunction(DynBitSet::new(), 0),
+                _ => ModuleType::Broadcast,
+            };
+            let (name, dests) = src.split_once(l, " -> ")?;
+            let name = if let ModuleType::Broadcast = ty {
+                name
+            } else {
+                &name[1..]
+            };
+            let id = names.intern(name);
+            for dest in dests.split(", ") {
+                outputs.add_edge(id, names.intern(dest));
+            }
+            modules.resize(names.len(), ModuleType::Untyped);
+            modules[id as usize] = ty;
+        }
+        modules.resize(names.len(), ModuleType::Untyped);
 
-    let mut i = 1;
-    while !deps.is_empty() {
-        let found = state.send_2(0, broadcaster, false, first_dep, &deps);
-        for dest in found {
-            if deps.remove(&dest) {
-                cycles.push(i);
+        let inputs = outputs.reversed();
+        for (id, ty) in modules.iter_mut().enumerate() {
+            if let ModuleType::Conjunction(_, count) = ty {
+                *count = inputs.neighbors(id as u32).len();
             }
         }
-        i += 1;
+        Ok(Sheet {
+            names,
+            modules,
+            outputs,
+            low_count: 0,
+            high_count: 0,
+        })
     }
-    lcm(cycles)
 }
 
 impl Sheet {
-    fn send(&mut self, sender_idx: usize, module_idx: usize, signal: bool) -> (usize, usize) {
+    fn broadcaster(&self) -> u32 {
+        self.modules
+            .iter()
+            .position(|m| matches!(m, ModuleType::Broadcast))
+            .unwrap() as u32
+    }
+
+    /// Updates the state of `dest` on receiving `signal` from `sender`, returning the
+    /// pulse it sends to its outputs, if any.
+    fn receive(&mut self, sender: u32, dest: u32, signal: bool) -> Option<bool> {
+        match &mut self.modules[dest as usize] {
+            ModuleType::FlipFlop(old) => {
+                if !signal {
+                    *old = !*old;
+                    Some(*old)
+                } else {
+                    None
+                }
+            }
+            ModuleType::Conjunction(high, count) => {
+                if signal {
+                    high.insert(sender as usize);
+                } else {
+                    high.remove(sender as usize);
+                }
+                Some(high.len() != *count)
+            }
+            ModuleType::Broadcast => Some(signal),
+            ModuleType::Untyped => None,
+        }
+    }
+
+    /// Bits of state of the given modules, one per flip-flop and per conjunction input, as
+    /// `(module, input)` pairs for [`Sheet::circuit_state`]. Flip-flops are their own input.
+    fn state_bits(
+        &self,
+        modules: impl IntoIterator<Item = u32>,
+        inputs: &AdjList,
+    ) -> Vec<(u32, u32)> {
+        let mut res = vec![];
+        for m in modules {
+            match self.modules[m as usize] {
+                ModuleType::FlipFlop(_) => res.push((m, m)),
+                ModuleType::Conjunction(..) => {
+                    res.extend(inputs.neighbors(m).iter().map(|&sender| (m, sender)))
+                }
+                ModuleType::Broadcast | ModuleType::Untyped => {}
+            }
+        }
+        res
+    }
+
+    fn circuit_state(&self, bits: &[(u32, u32)]) -> Vec<u64> {
+        let mut res = vec![0; bits.len().div_ceil(64)];
+        for (bit, &(m, sender)) in bits.iter().enumerate() {
+            let on = match &self.modules[m as usize] {
+                ModuleType::FlipFlop(on) => *on,
+                ModuleType::Conjunction(high, _) => high.contains(sender as usize),
+                ModuleType::Broadcast | ModuleType::Untyped => false,
+            };
+            res[bit / 64] |= u64::from(on) << (bit % 64);
+        }
+        res
+    }
+
+    fn send(&mut self, sender_idx: u32, module_idx: u32, signal: bool) -> (usize, usize) {
         let mut handle_queue = VecDeque::new();
         handle_queue.push_back((sender_idx, module_idx, signal));
         let mut res = (0, 0);
@@ -157,30 +245,9 @@ impl Sheet {
             } else {
                 res.0 += 1;
             }
-            if dest == usize::MAX {
-                continue;
-            }
-            let sender_name = self.modules[sender].name.clone();
-            let dest_module = &mut self.modules[dest];
-            let out = match &mut dest_module.ty {
-                ModuleType::FlipFlop(old) => {
-                    if !signal {
-                        *old = !*old;
-                        Some(*old)
-                    } else {
-                        None
-                    }
-                }
-                ModuleType::Conjunction(map) => {
-                    map.insert(sender_name, signal);
-                    Some(map.values().any(|v| !*v))
-                }
-                ModuleType::Broadcast => Some(signal),
-            };
-
-            if let Some(out) = out {
-                for module in &dest_module.outputs {
-                    handle_queue.push_front((dest, *module, out));
+            if let Some(out) = self.receive(sender, dest, signal) {
+                for &module in self.outputs.neighbors(dest) {
+                    handle_queue.push_front((dest, module, out));
                 }
             }
         }
@@ -190,46 +257,24 @@ impl Sheet {
 
     fn send_2(
         &mut self,
-        sender_idx: usize,
-        module_idx: usize,
+        sender_idx: u32,
+        module_idx: u32,
         signal: bool,
-        filter_dest: usize,
-        filter_high: &FxHashSet<usize>,
-    ) -> FxHashSet<usize> {
+        filter_dest: u32,
+        filter_high: &FxHashSet<u32>,
+    ) -> FxHashSet<u32> {
         let mut handle_queue = VecDeque::new();
         handle_queue.push_back((sender_idx, module_idx, signal));
 
         let mut res = FxHashSet::default();
 
         while let Some((sender, dest, signal)) = handle_queue.pop_back() {
-            if dest == usize::MAX {
-                continue;
-            }
             if signal && dest == filter_dest && filter_high.contains(&sender) {
                 res.insert(sender);
             }
-            let sender_name = self.modules[sender].name.clone();
-
-            let dest_module = &mut self.modules[dest];
-            let out = match &mut dest_module.ty {
-                ModuleType::FlipFlop(old) => {
-                    if !signal {
-                        *old = !*old;
-                        Some(*old)
-                    } else {
-                        None
-                    }
-                }
-                ModuleType::Conjunction(map) => {
-                    map.insert(sender_name.clone(), signal);
-                    Some(map.values().any(|v| !*v))
-                }
-                ModuleType::Broadcast => Some(signal),
-            };
-
-            if let Some(out) = out {
-                for module in &dest_module.outputs {
-                    handle_queue.push_front((dest, *module, out));
+            if let Some(out) = self.receive(sender, dest, signal) {
+                for &module in self.outputs.neighbors(dest) {
+                    handle_queue.push_front((dest, module, out));
                 }
             }
         }
@@ -237,18 +282,3 @@ impl Sheet {
         res
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"broadcaster -> a, b, c
-%a -> b
-%b -> c
-%c -> inv
-&inv -> a"#;
-        assert_eq!(part1(&parse(&input)), 32000000);
-    }
-}
@@ -1,83 +1,55 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, str::FromStr};
 
 use fxhash::FxHashSet;
-use itertools::Itertools;
 
-use super::day17::Direction;
-
-#[derive(Debug, Clone)]
-pub struct Grid {
-    tiles: Vec<Vec<Tile>>,
-}
+use crate::{
+    error::{ParseError, Source},
+    util::{geom::Vec2, grid::Grid, poly},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum Tile {
+pub enum Tile {
     Wall,
     Empty,
     Start,
 }
 
 #[aoc_generator(day21)]
-fn parse(input: &str) -> Grid {
-    Grid {
-        tiles: input
-            .lines()
-            .map(|l| {
-                l.chars()
-                    .map(|c| match c {
-                        '.' => Tile::Empty,
-                        '#' => Tile::Wall,
-                        'S' => Tile::Start,
-                        c => panic!("unknown {c}"),
-                    })
-                    .collect_vec()
-            })
-            .collect_vec(),
-    }
+pub(super) fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+    input.parse()
 }
 
 #[aoc(day21, part1)]
-pub fn part1(input: &Grid) -> usize {
+pub fn part1(input: &Grid<Tile>) -> usize {
     solve(input, 64, false)
 }
 
 #[aoc(day21, part2)]
-pub fn part2(input: &Grid) -> usize {
+pub fn part2(input: &Grid<Tile>) -> usize {
     let fin: usize = 26501365;
     // Edges are empty
-    let max = fin % input.tiles.len();
-
-    // Quadratic sequence, find the first three nums in the sequence and try
-    // to derive a formula
-    let seq_1 = solve(input, max, true);
-    let seq_2 = solve(input, max + input.tiles.len(), true);
-    let seq_3 = solve(input, max + 2 * input.tiles.len(), true);
-
-    let coeff_1 = ((seq_3 - seq_2) - (seq_2 - seq_1)) / 2;
-    let coeff_2 = (seq_2 - seq_1) - 3 * coeff_1;
-    let coeff_3 = seq_1 - coeff_2 - coeff_1;
-
-    let target = fin.div_ceil(input.tiles.len());
-    coeff_1 * target * target + coeff_2 * target + coeff_3
+    let max = fin % input.height();
+
+    // Quadratic sequence, find the first three nums in the sequence and
+    // extrapolate from there
+    let samples = (0..3)
+        .map(|i| solve(input, max + i * input.height(), true) as i128)
+        .collect::<Vec<_>>();
+    let target = (fin - max) / input.height();
+    poly::extrapolate(&samples, target as i128) as usize
 }
 
-fn solve(input: &Grid, max_steps: usize, p2: bool) -> usize {
+fn solve(input: &Grid<Tile>, max_steps: usize, p2: bool) -> usize {
     let mut to_move = VecDeque::new();
-    let start = input
-        .tiles
-        .iter()
-        .enumerate()
-        .flat_map(|(y, l)| l.iter().enumerate().map(move |(x, t)| (x, y, *t)))
-        .find(|(_, _, t)| *t == Tile::Start)
-        .unwrap();
-    to_move.push_back((start.0 as isize, start.1 as isize, 0));
+    let start = input.position(|t| *t == Tile::Start).unwrap();
+    to_move.push_back((start.cast().unwrap(), 0));
 
     let mut total = 0;
     let mut visited = FxHashSet::default();
     let target = max_steps % 2;
 
-    while let Some((x, y, steps)) = to_move.pop_back() {
-        if visited.contains(&(x, y)) {
+    while let Some((pos, steps)) = to_move.pop_back() {
+        if visited.contains(&pos) {
             continue;
         }
         if steps > max_steps {
@@ -86,17 +58,17 @@ fn solve(input: &Grid, max_steps: usize, p2: bool) -> usize {
         if steps % 2 == target {
             total += 1;
         }
-        visited.insert((x, y));
+        visited.insert(pos);
         if p2 {
-            for neigh in input.neighbors_2((x, y)) {
-                if !visited.contains(&(neigh.0, neigh.1)) {
-                    to_move.push_front((neigh.0, neigh.1, steps + 1));
+            for neigh in input.walkable_neighbors_wrapping(pos) {
+                if !visited.contains(&neigh) {
+                    to_move.push_front((neigh, steps + 1));
                 }
             }
         } else {
-            for neigh in input.neighbors((x, y)) {
-                if !visited.contains(&(neigh.0, neigh.1)) {
-                    to_move.push_front((neigh.0, neigh.1, steps + 1));
+            for neigh in input.walkable_neighbors(pos) {
+                if !visited.contains(&neigh) {
+                    to_move.push_front((neigh, steps + 1));
                 }
             }
         }
@@ -105,40 +77,38 @@ fn solve(input: &Grid, max_steps: usize, p2: bool) -> usize {
     total
 }
 
-impl Grid {
-    pub fn neighbors(&self, cur: (isize, isize)) -> impl Iterator<Item = (isize, isize)> + '_ {
-        [
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ]
-        .into_iter()
-        .map(move |d| (cur.0 + d.delta().0, cur.1 + d.delta().1))
-        .filter(move |(x, y)| {
-            self.tiles
-                .get(*y as usize)
-                .and_then(|r| r.get(*x as usize))
-                .is_some_and(|t| *t == Tile::Start || *t == Tile::Empty)
-        })
+impl Grid<Tile> {
+    pub fn walkable_neighbors(&self, cur: Vec2<isize>) -> impl Iterator<Item = Vec2<isize>> + '_ {
+        self.neighbors(cur)
+            .filter(move |&pos| self.get(pos).is_some_and(Tile::is_walkable))
     }
 
-    pub fn neighbors_2(&self, cur: (isize, isize)) -> impl Iterator<Item = (isize, isize)> + '_ {
-        [
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ]
-        .into_iter()
-        .map(move |d| (cur.0 + d.delta().0, cur.1 + d.delta().1))
-        .filter(move |(x, y)| {
-            let x = x.rem_euclid(self.tiles[0].len() as isize);
-            let y = y.rem_euclid(self.tiles.len() as isize);
-            self.tiles
-                .get(y as usize)
-                .and_then(|r| r.get(x as usize))
-                .is_some_and(|t| *t == Tile::Start || *t == Tile::Empty)
+    pub fn walkable_neighbors_wrapping(
+        &self,
+        cur: Vec2<isize>,
+    ) -> impl Iterator<Item = Vec2<isize>> + '_ {
+        Grid::<Tile>::neighbors_wrapping(cur)
+            .filter(move |&pos| self.get_wrapping(pos).is_walkable())
+    }
+}
+
+impl Tile {
+    fn is_walkable(&self) -> bool {
+        *self == Tile::Start || *self == Tile::Empty
+    }
+}
+
+impl FromStr for Grid<Tile> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::new(21, s).grid("'.', '#' or 'S'", |c| {
+            Some(match c {
+                '.' => Tile::Empty,
+                '#' => Tile::Wall,
+                'S' => Tile::Start,
+                _ => return None,
+            })
         })
     }
 }
@@ -160,6 +130,6 @@ mod tests {
 .##.#.####.
 .##..##.##.
 ..........."#;
-        assert_eq!(solve(&parse(&input), 6, false), 16);
+        assert_eq!(solve(&parse(input).unwrap(), 6, false), 16);
     }
 }
@@ -1,34 +1,28 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, str::FromStr};
 
 use itertools::Itertools;
-use regex::Regex;
+
+use crate::{
+    error::{parse_lines, ParseError, Source},
+    util::geom::Vec3,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Brick {
     id: usize,
-    min: (isize, isize, isize),
-    max: (isize, isize, isize),
+    min: Vec3<isize>,
+    max: Vec3<isize>,
 }
 
+const DOWN: Vec3<isize> = Vec3::new(0, 0, -1);
+
 #[aoc_generator(day22)]
-fn parse(input: &str) -> Vec<Brick> {
-    let regex = Regex::new(r#"(-?\d+)"#).unwrap();
-    input
-        .lines()
-        .enumerate()
-        .map(|(id, l)| {
-            let (ax, ay, az, bx, by, bz) = regex
-                .captures_iter(l)
-                .map(|s| s[1].parse::<isize>().unwrap())
-                .collect_tuple()
-                .unwrap();
-            Brick {
-                id,
-                min: (ax.min(bx), ay.min(by), az.min(bz)),
-                max: (ax.max(bx), ay.max(by), az.max(bz)),
-            }
-        })
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Vec<Brick>, ParseError> {
+    let mut bricks: Vec<Brick> = parse_lines(input)?;
+    for (id, brick) in bricks.iter_mut().enumerate() {
+        brick.id = id;
+    }
+    Ok(bricks)
 }
 
 #[aoc(day22, part1)]
@@ -108,14 +102,14 @@ pub fn part2(input: &[Brick]) -> usize {
 }
 
 fn move_down(bricks: &mut [Brick]) {
-    bricks.sort_unstable_by_key(|b| b.min.2);
+    bricks.sort_unstable_by_key(|b| b.min.z);
     loop {
         let mut changed = false;
         for i in 0..bricks.len() {
             loop {
                 let brick = bricks[i];
                 let moved = brick.moved_down();
-                if moved.min.2 <= 0 {
+                if moved.min.z <= 0 {
                     break;
                 }
                 if bricks
@@ -135,46 +129,30 @@ fn move_down(bricks: &mut [Brick]) {
     }
 }
 
+impl FromStr for Brick {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(22, s);
+        let [ax, ay, az, bx, by, bz]: [isize; 6] = src.ints_n(s, ",~", "x,y,z~x,y,z")?;
+        let (a, b) = (Vec3::new(ax, ay, az), Vec3::new(bx, by, bz));
+        Ok(Brick {
+            id: 0,
+            min: a.min_each(b),
+            max: a.max_each(b),
+        })
+    }
+}
+
 impl Brick {
     fn intersects(&self, other: &Self) -> bool {
-        (other.max.0 >= self.min.0 && other.min.0 <= self.max.0)
-            && (other.max.1 >= self.min.1 && other.min.1 <= self.max.1)
-            && (other.max.2 >= self.min.2 && other.min.2 <= self.max.2)
+        self.min.all_le(other.max) && other.min.all_le(self.max)
     }
 
     fn moved_down(&self) -> Self {
         let mut cpy = *self;
-        cpy.min.2 -= 1;
-        cpy.max.2 -= 1;
+        cpy.min += DOWN;
+        cpy.max += DOWN;
         cpy
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"1,0,1~1,2,1
-0,0,2~2,0,2
-0,2,3~2,2,3
-0,0,4~0,2,4
-2,0,5~2,2,5
-0,1,6~2,1,6
-1,1,8~1,1,9"#;
-        assert_eq!(part1(&parse(&input)), 5);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"1,0,1~1,2,1
-0,0,2~2,0,2
-0,2,3~2,2,3
-0,0,4~0,2,4
-2,0,5~2,2,5
-0,1,6~2,1,6
-1,1,8~1,1,9"#;
-        assert_eq!(part2(&parse(&input)), 7);
-    }
-}
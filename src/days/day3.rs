@@ -1,119 +1,255 @@
+use std::str::FromStr;
+
+use fxhash::FxHashMap;
 use itertools::Itertools;
-use regex::Regex;
+
+use crate::{
+    error::{ParseError, Source},
+    util::{
+        geom::Vec2,
+        graph::{AdjList, Graph},
+    },
+};
+
+/// Numbers and symbols of the input, in reading order.
+#[derive(Debug)]
+pub struct Diagram {
+    slots: Vec<Slot>,
+}
 
 #[derive(Debug)]
-pub struct Slot {
+struct Slot {
     ty: SlotType,
-    coords: (usize, usize),
+    coords: Vec2<usize>,
     len: usize,
 }
 
 #[derive(Debug)]
-
 enum SlotType {
     Number(i32),
     Symbol(char),
 }
 
+/// Cells around a number that count as adjacent to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Orthogonally next to one of the digits.
+    Four,
+    /// Diagonals included.
+    Eight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Number {
+    pub value: i32,
+    /// Position of the first digit.
+    pub pos: Vec2<usize>,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub char: char,
+    pub pos: Vec2<usize>,
+}
+
+/// Numbers and symbols of a schematic as a bipartite graph, linked when adjacent.
+///
+/// Numbers and symbols are identified by their index in [`Schematic::numbers`] and
+/// [`Schematic::symbols`], both in reading order.
+#[derive(Debug)]
+pub struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    /// Numbers are nodes `0..n`, symbols come right after them.
+    graph: AdjList,
+}
+
 #[aoc_generator(day3)]
-fn parse(input: &str) -> Vec<Slot> {
-    let regex = Regex::new(r#"(\d+)|([^\d\.\s]{1})"#).unwrap();
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            regex
-                .captures_iter(l)
-                .map(|m| {
-                    let m = m.get(1).or_else(|| m.get(2)).unwrap();
-                    let x = m.start();
-                    let st = m.as_str();
-                    let first = st.chars().next().unwrap();
-                    let ty = if first.is_ascii_digit() {
-                        SlotType::Number(st.parse().unwrap())
-                    } else {
-                        SlotType::Symbol(first)
-                    };
-                    Slot {
-                        ty,
-                        coords: (x, y),
-                        len: st.len(),
-                    }
-                })
-                .collect_vec()
-        })
-        .collect()
+pub(super) fn parse(input: &str) -> Result<Diagram, ParseError> {
+    input.parse()
 }
 
 #[aoc(day3, part1)]
-pub fn part1(input: &[Slot]) -> i32 {
-    let grid_max_x = input.iter().map(|s| s.coords.0).max().unwrap();
-    let grid_max_y = input.iter().map(|s| s.coords.1).max().unwrap();
-
-    let mut grid = vec![vec![false; grid_max_x + 1]; grid_max_y + 1];
-    for slot in input {
-        if let SlotType::Symbol(_) = slot.ty {
-            grid[slot.coords.1][slot.coords.0] = true;
+pub fn part1(input: &Diagram) -> i32 {
+    Schematic::new(input, Neighborhood::Eight, |_| true)
+        .part_numbers()
+        .map(|n| n.value)
+        .sum()
+}
+
+#[aoc(day3, part2)]
+pub fn part2(input: &Diagram) -> i32 {
+    let schematic = Schematic::new(input, Neighborhood::Eight, |c| c == '*');
+    schematic
+        .symbols_with_degree(2)
+        .map(|gear| {
+            schematic
+                .numbers_around(gear)
+                .map(|n| n.value)
+                .product::<i32>()
+        })
+        .sum()
+}
+
+impl Schematic {
+    /// Links numbers to the symbols around them, only keeping those for which `is_symbol` holds.
+    pub fn new(
+        diagram: &Diagram,
+        neighborhood: Neighborhood,
+        is_symbol: impl Fn(char) -> bool,
+    ) -> Self {
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+        for slot in &diagram.slots {
+            match slot.ty {
+                SlotType::Number(value) => numbers.push(Number {
+                    value,
+                    pos: slot.coords,
+                    len: slot.len,
+                }),
+                SlotType::Symbol(char) if is_symbol(char) => symbols.push(Symbol {
+                    char,
+                    pos: slot.coords,
+                }),
+                SlotType::Symbol(_) => {}
+            }
         }
-    }
 
-    let mut sum = 0;
-    'slots: for slot in input {
-        if let SlotType::Number(n) = slot.ty {
-            let (min_x, min_y) = slot.start();
-            let (max_x, max_y) = slot.end();
-            for y in min_y.saturating_sub(1)..=max_y.saturating_add(1).min(grid_max_y) {
-                for x in min_x.saturating_sub(1)..=max_x.saturating_add(1).min(grid_max_x) {
-                    if grid[y][x] {
-                        sum += n;
-                        continue 'slots;
-                    }
+        let at: FxHashMap<Vec2<isize>, usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.pos.cast().unwrap(), i))
+            .collect();
+        let mut graph = AdjList::new(numbers.len() + symbols.len());
+        for (i, number) in numbers.iter().enumerate() {
+            for pos in number.surroundings(neighborhood) {
+                if let Some(&symbol) = at.get(&pos) {
+                    graph.add_undirected(i as u32, (numbers.len() + symbol) as u32);
                 }
             }
         }
-    }
-    sum
-}
 
-#[aoc(day3, part2)]
-pub fn part2(input: &[Slot]) -> i32 {
-    let grid_max_x = input.iter().map(|s| s.coords.0).max().unwrap();
-    let grid_max_y = input.iter().map(|s| s.coords.1).max().unwrap();
-
-    let mut grid = vec![vec![(0i32, 0i32); grid_max_x + 1]; grid_max_y + 1];
-    for slot in input {
-        if let SlotType::Symbol(_) = slot.ty {
-            grid[slot.coords.1][slot.coords.0] = (1, 0);
+        Self {
+            numbers,
+            symbols,
+            graph,
         }
     }
 
-    for slot in input {
-        if let SlotType::Number(n) = slot.ty {
-            let (min_x, min_y) = slot.start();
-            let (max_x, max_y) = slot.end();
-            for y in min_y.saturating_sub(1)..=max_y.saturating_add(1).min(grid_max_y) {
-                for x in min_x.saturating_sub(1)..=max_x.saturating_add(1).min(grid_max_x) {
-                    let (prod, num) = grid[y][x];
-                    grid[y][x] = (prod * n, num + 1);
-                }
-            }
-        }
+    /// Parses the schematic straight from the puzzle input, see [`Schematic::new`].
+    pub fn parse(
+        input: &str,
+        neighborhood: Neighborhood,
+        is_symbol: impl Fn(char) -> bool,
+    ) -> Result<Self, ParseError> {
+        Ok(Self::new(&input.parse()?, neighborhood, is_symbol))
     }
 
-    input
-        .iter()
-        .filter(|s| matches!(s.ty, SlotType::Symbol('*')) && grid[s.coords.1][s.coords.0].1 == 2)
-        .map(|s| grid[s.coords.1][s.coords.0].0)
-        .sum()
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Numbers adjacent to the symbol with the given index.
+    pub fn numbers_around(&self, symbol: usize) -> impl Iterator<Item = &Number> + '_ {
+        let node = (self.numbers.len() + symbol) as u32;
+        self.graph
+            .neighbors(node)
+            .iter()
+            .map(|&n| &self.numbers[n as usize])
+    }
+
+    /// Symbols adjacent to the number with the given index.
+    pub fn symbols_around(&self, number: usize) -> impl Iterator<Item = &Symbol> + '_ {
+        self.graph
+            .neighbors(number as u32)
+            .iter()
+            .map(|&s| &self.symbols[s as usize - self.numbers.len()])
+    }
+
+    /// Indices of the symbols adjacent to exactly `k` numbers.
+    pub fn symbols_with_degree(&self, k: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len())
+            .filter(move |&s| self.graph.neighbors((self.numbers.len() + s) as u32).len() == k)
+    }
+
+    /// Numbers adjacent to at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> + '_ {
+        self.numbers_by_adjacency(true)
+    }
+
+    /// Numbers without any adjacent symbol.
+    pub fn orphans(&self) -> impl Iterator<Item = &Number> + '_ {
+        self.numbers_by_adjacency(false)
+    }
+
+    fn numbers_by_adjacency(&self, adjacent: bool) -> impl Iterator<Item = &Number> + '_ {
+        self.numbers
+            .iter()
+            .enumerate()
+            .filter(move |&(i, _)| self.graph.neighbors(i as u32).is_empty() != adjacent)
+            .map(|(_, n)| n)
+    }
 }
 
-impl Slot {
-    fn start(&self) -> (usize, usize) {
-        (self.coords.0, self.coords.1)
+impl Number {
+    /// Cells around the digits, possibly out of bounds.
+    fn surroundings(&self, neighborhood: Neighborhood) -> Vec<Vec2<isize>> {
+        let start = self.pos.cast::<isize>().unwrap();
+        let end = start + Vec2::new(self.len as isize - 1, 0);
+        match neighborhood {
+            Neighborhood::Four => (start.x..=end.x)
+                .flat_map(|x| [Vec2::new(x, start.y - 1), Vec2::new(x, start.y + 1)])
+                .chain([start - Vec2::new(1, 0), end + Vec2::new(1, 0)])
+                .collect(),
+            Neighborhood::Eight => (start.y - 1..=start.y + 1)
+                .cartesian_product(start.x - 1..=end.x + 1)
+                .map(|(y, x)| Vec2::new(x, y))
+                .collect(),
+        }
     }
+}
 
-    fn end(&self) -> (usize, usize) {
-        (self.coords.0 + self.len - 1, self.coords.1)
+impl FromStr for Diagram {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(3, s);
+        let mut slots = vec![];
+        for (y, l) in s.lines().enumerate() {
+            // Columns count characters, byte offsets are only used for slicing
+            let mut chars = l.char_indices().enumerate().peekable();
+            while let Some((x, (start, c))) = chars.next() {
+                if c.is_ascii_digit() {
+                    let mut len = 1;
+                    let mut end = start + 1;
+                    while let Some(&(_, (i, d))) = chars.peek() {
+                        if !d.is_ascii_digit() {
+                            break;
+                        }
+                        chars.next();
+                        len += 1;
+                        end = i + 1;
+                    }
+                    slots.push(Slot {
+                        ty: SlotType::Number(src.parse(&l[start..end], "part number")?),
+                        coords: Vec2::new(x, y),
+                        len,
+                    });
+                } else if c != '.' && !c.is_whitespace() {
+                    slots.push(Slot {
+                        ty: SlotType::Symbol(c),
+                        coords: Vec2::new(x, y),
+                        len: 1,
+                    });
+                }
+            }
+        }
+        Ok(Diagram { slots })
     }
 }
 
@@ -121,9 +257,7 @@ impl Slot {
 mod tests {
     use super::*;
 
-    #[test]
-    fn part1_example() {
-        let input = r#"467..114..
+    const INPUT: &str = "467..114..
 ...*......
 ..35..633.
 ......#...
@@ -132,22 +266,59 @@ mod tests {
 ..592.....
 ......755.
 ...$.*....
-.664.598.."#;
-        assert_eq!(part1(&parse(&input)), 4361);
+.664.598..";
+
+    #[test]
+    fn queries() {
+        let diagram = parse(INPUT).unwrap();
+        let schematic = Schematic::new(&diagram, Neighborhood::Eight, |_| true);
+        let values = |numbers: Vec<&Number>| numbers.iter().map(|n| n.value).collect_vec();
+        assert_eq!(values(schematic.orphans().collect()), [114, 58]);
+        assert_eq!(schematic.symbols().len(), 6);
+
+        // The '*' next to 617 only
+        let star = schematic
+            .symbols()
+            .iter()
+            .position(|s| s.pos == Vec2::new(3, 4))
+            .unwrap();
+        assert_eq!(values(schematic.numbers_around(star).collect()), [617]);
+        assert_eq!(schematic.symbols_with_degree(1).count(), 4);
+        let first = schematic.symbols_around(0).collect_vec();
+        assert_eq!((first.len(), first[0].char), (1, '*'));
+
+        // Only '#' and '+'
+        let schematic = Schematic::parse(INPUT, Neighborhood::Eight, |c| "#+".contains(c)).unwrap();
+        assert_eq!(values(schematic.part_numbers().collect()), [633, 592]);
+        assert_eq!(schematic.symbols_with_degree(0).count(), 0);
     }
 
     #[test]
-    fn part2_example() {
-        let input = r#"467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598.."#;
-        assert_eq!(part2(&parse(&input)), 467835);
+    fn neighborhoods() {
+        let diagram = parse("1.2.\n.*..\n34..").unwrap();
+        let eight = Schematic::new(&diagram, Neighborhood::Eight, |_| true);
+        assert_eq!(eight.numbers_around(0).count(), 3);
+        let four = Schematic::new(&diagram, Neighborhood::Four, |_| true);
+        assert_eq!(four.part_numbers().map(|n| n.value).collect_vec(), [34]);
+        assert_eq!(four.orphans().count(), 2);
+    }
+
+    #[test]
+    fn non_ascii_columns() {
+        // 'é' takes two bytes but a single column
+        let diagram = parse("é12\n...£").unwrap();
+        let schematic = Schematic::new(&diagram, Neighborhood::Eight, |_| true);
+        assert_eq!(schematic.numbers()[0].pos, Vec2::new(1, 0));
+        let symbols = schematic
+            .symbols()
+            .iter()
+            .map(|s| (s.char, s.pos))
+            .collect_vec();
+        assert_eq!(symbols, [('é', Vec2::new(0, 0)), ('£', Vec2::new(3, 1))]);
+        assert_eq!(schematic.part_numbers().count(), 1);
+        assert_eq!(schematic.symbols_around(0).count(), 2);
+
+        let err = parse("..\n.99999999999").unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 2));
     }
 }
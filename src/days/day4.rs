@@ -1,6 +1,11 @@
+use std::{error, fmt, str::FromStr};
+
 use fxhash::FxHashSet;
-use itertools::Itertools;
-use regex::Regex;
+
+use crate::{
+    error::{parse_lines, ParseError, Source},
+    util::graph::{AdjList, Graph},
+};
 
 #[derive(Debug)]
 pub struct Card {
@@ -9,29 +14,36 @@ pub struct Card {
     own: FxHashSet<i32>,
 }
 
+/// Copies of every card once all the won ones are scratched.
+///
+/// A card with `n` matching numbers wins a copy of each of the next `n` ids, so any of them
+/// missing from the table is a copy lost.
+#[derive(Debug)]
+pub struct Cascade {
+    /// Sorted, everything else is indexed like them.
+    ids: Vec<i32>,
+    copies: Vec<usize>,
+    /// Edge from each card to those it wins a copy of, once for each of its own copies.
+    wins: AdjList,
+}
+
+/// Two cards share an id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateId(pub i32);
+
 #[aoc_generator(day4)]
-fn parse(input: &str) -> Vec<Card> {
-    let regex = Regex::new(r#"(\d+)"#).unwrap();
-    input
-        .lines()
-        .map(|l| {
-            let (winners, own) = l.split(" | ").collect_tuple().unwrap();
-            let winners = regex
-                .captures_iter(winners)
-                .map(|c| c.get(1).unwrap().as_str().parse().unwrap())
-                .collect_vec();
-            let own = regex
-                .captures_iter(own)
-                .map(|c| c.get(1).unwrap().as_str().parse().unwrap())
-                .collect();
-
-            Card {
-                id: winners[0],
-                winners: winners[1..].into_iter().copied().collect(),
-                own,
-            }
-        })
-        .collect()
+pub(super) fn parse(input: &str) -> Result<Vec<Card>, ParseError> {
+    let cards: Vec<Card> = parse_lines(input)?;
+    // Checked here rather than by the cascade so that the duplicate can be pointed at
+    let mut ids = FxHashSet::default();
+    for (card, line) in cards.iter().zip(input.lines()) {
+        if !ids.insert(card.id) {
+            let src = Source::new(4, input);
+            let id = line.split_once(':').map_or(line, |(id, _)| id);
+            return Err(src.error(id.trim_start_matches("Card").trim_start(), "unique card id"));
+        }
+    }
+    Ok(cards)
 }
 
 #[aoc(day4, part1)]
@@ -39,7 +51,7 @@ pub fn part1(input: &[Card]) -> i32 {
     input
         .iter()
         .map(|c| {
-            let count = c.own.intersection(&c.winners).count();
+            let count = c.matches();
             if count > 0 {
                 1 << count - 1
             } else {
@@ -51,49 +63,160 @@ pub fn part1(input: &[Card]) -> i32 {
 
 #[aoc(day4, part2)]
 pub fn part2(input: &[Card]) -> usize {
-    let min_id = input.iter().map(|c| c.id).min().unwrap() as usize;
-    let max_id = input.iter().map(|c| c.id).max().unwrap() as usize;
+    Cascade::new(input)
+        .expect("the generator rejects duplicate ids")
+        .total()
+}
 
-    // (Card count, cached winners)
-    let mut counts = vec![(1usize, 0usize); max_id];
+impl Card {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
 
-    for (i, c) in input.iter().enumerate() {
-        counts[i].1 = c.own.intersection(&c.winners).count();
+    pub fn matches(&self) -> usize {
+        self.own.intersection(&self.winners).count()
     }
+}
+
+impl Cascade {
+    /// Plays out the cards, in any order.
+    pub fn new(cards: &[Card]) -> Result<Self, DuplicateId> {
+        let mut cards = cards.iter().collect::<Vec<_>>();
+        cards.sort_unstable_by_key(|c| c.id);
+        if let Some(w) = cards.windows(2).find(|w| w[0].id == w[1].id) {
+            return Err(DuplicateId(w[0].id));
+        }
+        let ids = cards.iter().map(|c| c.id).collect::<Vec<_>>();
 
-    for i in min_id - 1..max_id {
-        let (count, winners) = counts[i];
-        for w in 1..=winners {
-            counts[i + w].0 += count;
+        // Cards only win later ones, so a single pass in id order settles every count
+        let mut copies = vec![1; cards.len()];
+        let mut wins = AdjList::new(cards.len());
+        for (i, card) in cards.iter().enumerate() {
+            let last = card.id.saturating_add(card.matches() as i32);
+            let end = ids.partition_point(|&id| id <= last);
+            for j in i + 1..end {
+                copies[j] += copies[i];
+                wins.add_edge(i as u32, j as u32);
+            }
         }
+
+        Ok(Self { ids, copies, wins })
+    }
+
+    /// Ids of the cards, in increasing order.
+    pub fn ids(&self) -> &[i32] {
+        &self.ids
+    }
+
+    /// Copies of card `id` in the end, the original included.
+    pub fn copies(&self, id: i32) -> Option<usize> {
+        self.index(id).map(|i| self.copies[i])
+    }
+
+    /// Cards that card `id` wins a copy of, along with how many copies it wins in total:
+    /// one for each of its own copies.
+    pub fn wins(&self, id: i32) -> impl Iterator<Item = (i32, usize)> + '_ {
+        self.index(id).into_iter().flat_map(move |i| {
+            self.wins
+                .neighbors(i as u32)
+                .iter()
+                .map(move |&j| (self.ids[j as usize], self.copies[i]))
+        })
+    }
+
+    /// Cards that win copies of card `id`.
+    pub fn won_by(&self, id: i32) -> impl Iterator<Item = i32> + '_ {
+        let target = self.index(id).map(|i| i as u32);
+        (0..self.ids.len())
+            .filter(move |&i| target.is_some_and(|t| self.wins.neighbors(i as u32).contains(&t)))
+            .map(|i| self.ids[i])
+    }
+
+    /// Cards in the end.
+    pub fn total(&self) -> usize {
+        self.copies.iter().sum()
     }
 
-    counts.into_iter().map(|(x, _)| x).sum()
+    /// Cards in the end up to card `max_id` included. Later cards can't win earlier ones, so
+    /// this is also the total of a table ending there.
+    pub fn total_up_to(&self, max_id: i32) -> usize {
+        let end = self.ids.partition_point(|&id| id <= max_id);
+        self.copies[..end].iter().sum()
+    }
+
+    fn index(&self, id: i32) -> Option<usize> {
+        self.ids.binary_search(&id).ok()
+    }
 }
 
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(4, s);
+        let (id, numbers) = src.split_once(s, ":")?;
+        let id = src.parse(src.strip_prefix(id, "Card")?.trim_start(), "card id")?;
+        let (winners, own) = src.split_once(numbers, " | ")?;
+
+        Ok(Card {
+            id,
+            winners: src.ints(winners, " ", "number").collect::<Result<_, _>>()?,
+            own: src.ints(own, " ", "number").collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl fmt::Display for DuplicateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate card id {}", self.0)
+    }
+}
+
+impl error::Error for DuplicateId {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn cards(input: &str) -> Vec<Card> {
+        parse_lines(input).unwrap()
+    }
+
     #[test]
-    fn part1_example() {
-        let input = r#"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#;
-        assert_eq!(part1(&parse(&input)), 13);
+    fn gaps_and_order() {
+        // Card 3 is missing: card 1 only wins a copy of card 2, which wins card 4
+        let input = cards(
+            "Card 4: 1 | 2\n\
+             Card 1: 1 2 | 1 2\n\
+             Card 7: 1 | 1\n\
+             Card 2: 5 6 | 6 5",
+        );
+        let cascade = Cascade::new(&input).unwrap();
+        assert_eq!(cascade.ids(), [1, 2, 4, 7]);
+        let copies = cascade.ids().iter().map(|&id| cascade.copies(id).unwrap());
+        assert!(copies.eq([1, 2, 3, 1]));
+        assert_eq!(cascade.copies(3), None);
+        assert_eq!(cascade.total(), 7);
+        assert_eq!(cascade.total_up_to(3), 3);
+        assert_eq!(cascade.total_up_to(0), 0);
+
+        assert_eq!(cascade.wins(1).collect::<Vec<_>>(), [(2, 1)]);
+        assert_eq!(cascade.wins(2).collect::<Vec<_>>(), [(4, 2)]);
+        assert_eq!(cascade.wins(7).count(), 0);
+        assert_eq!(cascade.won_by(4).collect::<Vec<_>>(), [2]);
+        assert_eq!(cascade.won_by(2).collect::<Vec<_>>(), [1]);
+        assert_eq!(cascade.won_by(1).count(), 0);
     }
 
     #[test]
-    fn part2_example() {
-        let input = r#"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#;
-        assert_eq!(part2(&parse(&input)), 30);
+    fn duplicates() {
+        let input = cards("Card 2: 1 | 1\nCard 1: 1 | 1\nCard 2: 3 | 4");
+        let err = Cascade::new(&input).unwrap_err();
+        assert_eq!(err, DuplicateId(2));
+        assert_eq!(err.to_string(), "duplicate card id 2");
+
+        let err = parse("Card 2: 1 | 1\nCard 1: 1 | 1\nCard  2: 3 | 4").unwrap_err();
+        assert_eq!((err.line(), err.column()), (3, 7));
+        assert_eq!(err.found(), "2");
     }
 }
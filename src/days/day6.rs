@@ -1,5 +1,8 @@
+use std::str::FromStr;
+
 use itertools::Itertools;
-use regex::Regex;
+
+use crate::error::{ParseError, Source};
 
 #[derive(Debug)]
 pub struct Race {
@@ -7,35 +10,28 @@ pub struct Race {
     distance: i64,
 }
 
+#[derive(Debug)]
+pub struct Sheet {
+    races: Vec<Race>,
+}
+
 #[aoc_generator(day6)]
-fn parse(input: &str) -> Vec<Race> {
-    let regex = Regex::new(r#"(\d+)"#).unwrap();
-    let (time, distance) = input
-        .lines()
-        .map(|l| {
-            regex
-                .captures_iter(l)
-                .map(|c| c.get(1).unwrap().as_str().parse().unwrap())
-        })
-        .collect_tuple()
-        .unwrap();
-    time.zip(distance)
-        .map(|(time, distance)| Race::new(time, distance))
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Sheet, ParseError> {
+    input.parse()
 }
 
 #[aoc(day6, part1)]
-pub fn part1(input: &[Race]) -> i64 {
-    input.iter().map(Race::num_ways).product()
+pub fn part1(input: &Sheet) -> i64 {
+    input.races.iter().map(Race::num_ways).product()
 }
 
 #[aoc(day6, part2)]
-pub fn part2(input: &[Race]) -> i64 {
+pub fn part2(input: &Sheet) -> i64 {
     // Problem is still really easy, let's try to think of a way to merge the numbers without
     // having to parse the input again :')
     Race::new(
-        stack_numbers(input.iter().map(|r| r.time)),
-        stack_numbers(input.iter().map(|r| r.distance)),
+        stack_numbers(input.races.iter().map(|r| r.time)),
+        stack_numbers(input.races.iter().map(|r| r.distance)),
     )
     .num_ways()
 }
@@ -51,11 +47,19 @@ fn stack_numbers(nums: impl DoubleEndedIterator<Item = i64>) -> i64 {
 
 impl Race {
     pub fn new(time: i64, distance: i64) -> Self {
-        assert!(time as f64 > 2.0 * (distance as f64).sqrt());
         Self { time, distance }
     }
 
+    /// Holding the button for half the time goes the farthest, is it enough to beat the record?
+    pub fn can_win(&self) -> bool {
+        self.time * self.time > 4 * self.distance
+    }
+
     pub fn num_ways(&self) -> i64 {
+        if !self.can_win() {
+            return 0;
+        }
+
         // Less dev time (initial solution): simple brute force, could even optimize with binary search
         // (P2 with no optimizations runs in ~25ms on real inputs)
 
@@ -69,21 +73,45 @@ impl Race {
     }
 }
 
+impl FromStr for Sheet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(6, s);
+        let [time, distance] = src.array(s, s.lines(), "time and distance lines")?;
+        let time = src.strip_prefix(time, "Time:")?;
+        let distance = src.strip_prefix(distance, "Distance:")?;
+        let numbers = |l| src.ints(l, " ", "number").collect::<Result<Vec<_>, _>>();
+        let (times, distances) = (numbers(time)?, numbers(distance)?);
+        if times.len() != distances.len() {
+            return Err(src.error(
+                &distance[distance.len()..],
+                format!("{} distances", times.len()),
+            ));
+        }
+
+        let races = times
+            .into_iter()
+            .zip(distances)
+            .map(|(time, distance)| Race::new(time, distance))
+            .collect_vec();
+        if let Some(i) = races.iter().position(|r| !r.can_win()) {
+            let record = distance.split_whitespace().nth(i).unwrap_or(distance);
+            return Err(src.error(record, "record that can be beaten"));
+        }
+        Ok(Sheet { races })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn part1_example() {
-        let input = r#"Time:      7  15   30
-Distance:  9  40  200"#;
-        assert_eq!(part1(&parse(&input)), 288);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"Time:      7  15   30
-Distance:  9  40  200"#;
-        assert_eq!(part2(&parse(&input)), 71503);
+    fn unbeatable_record() {
+        let err = parse("Time:      7  15\nDistance:  9  57").unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 15));
+        assert_eq!(err.found(), "57");
+        assert_eq!(Race::new(4, 4).num_ways(), 0);
     }
 }
@@ -1,5 +1,9 @@
+use std::str::FromStr;
+
 use itertools::{repeat_n, Itertools};
 
+use crate::error::{parse_lines, ParseError, Source};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Hand {
     pub dup_score: i32,
@@ -14,16 +18,8 @@ pub struct HandDef {
 }
 
 #[aoc_generator(day7)]
-fn parse(input: &str) -> Vec<HandDef> {
-    input
-        .lines()
-        .map(|l| {
-            let (chars, bid) = l.split_whitespace().collect_tuple().unwrap();
-            let chars = chars.chars().collect_vec();
-            let bid = bid.parse().unwrap();
-            HandDef { name: chars, bid }
-        })
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Vec<HandDef>, ParseError> {
+    parse_lines(input)
 }
 
 #[aoc(day7, part1)]
@@ -48,6 +44,28 @@ pub fn part2(input: &[HandDef]) -> usize {
         .sum()
 }
 
+impl FromStr for HandDef {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(7, s);
+        let [chars, bid] = src.array(s, s.split_whitespace(), "hand and bid")?;
+        if let Some((i, c)) = chars
+            .char_indices()
+            .find(|(_, c)| !"AKQJT98765432".contains(*c))
+        {
+            return Err(src.error(&chars[i..i + c.len_utf8()], "card"));
+        }
+        if chars.len() != 5 {
+            return Err(src.error(chars, "hand of 5 cards"));
+        }
+        Ok(HandDef {
+            name: chars.chars().collect_vec(),
+            bid: src.parse(bid, "bid")?,
+        })
+    }
+}
+
 impl HandDef {
     pub fn calc(&self) -> Hand {
         let mut chars = self.name.clone();
@@ -122,11 +140,12 @@ impl HandDef {
             ['A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2'],
             js,
         )
-        .multi_cartesian_product().chain(std::iter::once(vec![]))
+        .multi_cartesian_product()
+        .chain(std::iter::once(vec![]))
         {
             if perm.is_empty() && js != 0 {
                 continue;
-            } 
+            }
             chars.clone_into(&mut buf);
             buf.extend(perm);
             buf.sort_unstable();
@@ -137,10 +156,10 @@ impl HandDef {
                 .collect_vec();
             max_dups.sort_unstable();
             let dup_score = match &max_dups[..] {
-                &[5] =>  {
+                &[5] => {
                     max_score = 6;
                     break;
-                },
+                }
                 &[1, 4] => 5,
                 &[2, 3] => 4,
                 &[1, 1, 3] => 3,
@@ -161,28 +180,3 @@ impl HandDef {
         }
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483"#;
-        assert_eq!(part1(&parse(input)), 6440);
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483"#;
-        assert_eq!(part2(&parse(input)), 5905);
-    }
-}
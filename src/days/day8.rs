@@ -1,57 +1,35 @@
-use fxhash::FxHashMap;
+use std::str::FromStr;
+
 use itertools::Itertools;
-use regex::Regex;
+
+use crate::{
+    error::{ParseError, Source},
+    util::{
+        cycle::{self, Schedule},
+        graph::{AdjList, Csr, Graph, Interner},
+    },
+};
 
 #[derive(Debug)]
 pub struct Sheet {
     directions: Vec<bool>,
-    nodes: Vec<Node>,
-}
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Node {
-    id: String,
-    left: String,
-    right: String,
+    names: Interner,
+    /// Every node has exactly two neighbours: left, then right.
+    graph: Csr,
 }
 
 #[aoc_generator(day8)]
-fn parse(input: &str) -> Sheet {
-    let directions = input
-        .lines()
-        .next()
-        .unwrap()
-        .chars()
-        .map(|c| c == 'R')
-        .collect_vec();
-    let regex = Regex::new(r#"(\w+) = \((\w+), (\w+)\)"#).unwrap();
-    let nodes = input
-        .lines()
-        .skip(2)
-        .map(|l| {
-            let cap = regex.captures_iter(l).next().unwrap();
-            Node {
-                id: cap[1].to_string(),
-                left: cap[2].to_string(),
-                right: cap[3].to_string(),
-            }
-        })
-        .collect_vec();
-    Sheet { directions, nodes }
+pub(super) fn parse(input: &str) -> Result<Sheet, ParseError> {
+    input.parse()
 }
 
 #[aoc(day8, part1)]
 pub fn part1(input: &Sheet) -> usize {
-    let nodes: FxHashMap<_, _> = input.nodes.iter().map(|n| (n.id.clone(), n)).collect();
-    let mut cur = &nodes["AAA"];
-    let dest = &nodes["ZZZ"];
+    let mut cur = input.names.get("AAA").unwrap();
+    let dest = input.names.get("ZZZ").unwrap();
     for (i, &dir) in input.directions.iter().cycle().enumerate() {
-        if dir {
-            cur = &&nodes[&cur.right];
-        } else {
-            cur = &&nodes[&cur.left];
-        }
-        if std::ptr::eq(cur, dest) {
+        cur = input.graph.neighbors(cur)[dir as usize];
+        if cur == dest {
             return i + 1;
         }
     }
@@ -64,93 +42,80 @@ pub fn part2(input: &Sheet) -> usize {
     // Printed the numbers, tried to pop them into WolframAlpha to quickly get the lcm, worked! :o
     // I thought you had to distinguish between R/L after the Z, but I guess not...
 
-    let nodes: FxHashMap<_, _> = input.nodes.iter().map(|n| (n.id.clone(), n)).collect();
-    let mut memo_state: FxHashMap<usize, usize> = FxHashMap::default();
-    let mut cur = input
-        .nodes
+    // Now rather than assuming it, find each ghost's cycle over (node, instruction) states
+    // and when it's on a Z along the way.
+    let is_end = input
+        .names
         .iter()
-        .filter(|n| n.id.ends_with("A"))
+        .map(|(_, name)| name.ends_with('Z'))
         .collect_vec();
-    for (i, &dir) in input.directions.iter().cycle().enumerate() {
-        for (j, node) in cur.iter_mut().enumerate() {
-            *node = &&nodes[if dir { &node.right } else { &node.left }];
-            if node.id.ends_with("Z") {
-                if !memo_state.contains_key(&j) {
-                    memo_state.insert(j, i + 1);
+    let schedules = input
+        .names
+        .iter()
+        .filter(|(_, name)| name.ends_with('A'))
+        .map(|(start, _)| {
+            let mut hits = vec![];
+            let mut i = 0;
+            let step = |(node, dir): (u32, usize)| {
+                i += 1;
+                let next = input.graph.neighbors(node)[input.directions[dir] as usize];
+                if is_end[next as usize] {
+                    hits.push(i);
                 }
-            }
-        }
-        if memo_state.len() == cur.len() {
-            return lcm(memo_state.into_values());
-        }
-    }
-    unreachable!()
+                (next, (dir + 1) % input.directions.len())
+            };
+            let (cycle, _) = cycle::fingerprint((start, 0), step, |&state| state);
+            hits.retain(|&h| h < cycle.mu + cycle.lambda);
+            Schedule { cycle, hits }
+        })
+        .collect_vec();
+    cycle::first_common_hit(&schedules).expect("ghosts never all reach a Z at once")
 }
 
-fn gcd(mut n: usize, mut d: usize) -> usize {
-    while n != 0 && d != 0 {
-        n %= d;
-        if n == 0 {
-            break;
+impl FromStr for Sheet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(8, s);
+        let mut lines = s.lines();
+        let directions = lines.next().unwrap_or_default();
+        let directions = directions
+            .char_indices()
+            .map(|(i, c)| match c {
+                'L' => Ok(false),
+                'R' => Ok(true),
+                _ => Err(src.error(&directions[i..i + c.len_utf8()], "L or R")),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut names = Interner::new();
+        let mut defined = vec![];
+        let mut edges = vec![];
+        for l in lines.skip(1) {
+            let (id, children) = src.split_once(l, " = ")?;
+            let children = src.strip_prefix(children, "(")?;
+            let children = src.strip_suffix(children, ")")?;
+            let (left, right) = src.split_once(children, ", ")?;
+            let id = names.intern(id);
+            defined.resize(names.len(), false);
+            defined[id as usize] = true;
+            edges.push((id, left, right));
         }
-        d %= n;
-    }
-    n + d
-}
-
-pub fn lcm(nums: impl IntoIterator<Item = usize>) -> usize {
-    nums.into_iter().fold(0, |i, c| match (i, c) {
-        (0, a) => a,
-        (n, b) => n * b / gcd(n, b),
-    })
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example1() {
-        let input = r#"RL
-
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)"#;
-        assert_eq!(part1(&parse(&input)), 2);
-    }
-
-    #[test]
-    fn part1_example2() {
-        let input = r#"LLR
 
-AAA = (BBB, BBB)
-BBB = (AAA, ZZZ)
-ZZZ = (ZZZ, ZZZ)"#;
-        assert_eq!(part1(&parse(&input)), 6);
-    }
-
-    #[test]
-    fn lcm() {
-        assert_eq!(12, super::lcm([1, 2, 3, 12]));
-        assert_eq!(20, super::lcm([5, 4]));
-    }
-
-    #[test]
-    fn part2_example() {
-        let input = r#"LRLR
-
-11A = (11B, XXX)
-11B = (XXX, 11Z)
-11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)"#;
-        assert_eq!(part2(&parse(&input)), 6);
+        let mut graph = AdjList::new(names.len());
+        for &(id, left, right) in &edges {
+            for child in [left, right] {
+                let child_id = names
+                    .get(child)
+                    .filter(|&c| defined[c as usize])
+                    .ok_or_else(|| src.error(child, "defined node"))?;
+                graph.add_edge(id, child_id);
+            }
+        }
+        Ok(Sheet {
+            directions,
+            names,
+            graph: Csr::from(&graph),
+        })
     }
 }
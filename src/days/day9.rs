@@ -1,84 +1,38 @@
-use itertools::Itertools;
-use regex::Regex;
+use std::str::FromStr;
+
+use crate::{
+    error::{parse_lines, ParseError, Source},
+    util::poly,
+};
 
 #[derive(Debug)]
-pub struct Sequence(Vec<i32>);
+pub struct Sequence(Vec<i128>);
 
 #[aoc_generator(day9)]
-fn parse(input: &str) -> Vec<Sequence> {
-    let regex = Regex::new(r#"(-?\d+)"#).unwrap();
-    input
-        .lines()
-        .map(|l| {
-            Sequence(
-                regex
-                    .captures_iter(l)
-                    .map(|c| c[1].parse().unwrap())
-                    .collect_vec(),
-            )
-        })
-        .collect_vec()
+pub(super) fn parse(input: &str) -> Result<Vec<Sequence>, ParseError> {
+    parse_lines(input)
 }
 
 #[aoc(day9, part1)]
-pub fn part1(input: &[Sequence]) -> i32 {
+pub fn part1(input: &[Sequence]) -> i128 {
     input
         .iter()
-        .map(|s| {
-            let mut next = *s.0.last().unwrap();
-            let mut buf = s.0.clone();
-            while buf.iter().any(|&n| n != 0) {
-                let old = buf.clone();
-                buf.clear();
-                for window in old.windows(2) {
-                    let &[a, b] = window else { panic!() };
-                    buf.push(b - a);
-                }
-                next += buf.last().unwrap();
-            }
-            next
-        })
+        .map(|s| poly::extrapolate(&s.0, s.0.len() as i128))
         .sum()
 }
 
 #[aoc(day9, part2)]
-pub fn part2(input: &[Sequence]) -> i32 {
-    input
-        .iter()
-        .map(|s| {
-            let mut next = *s.0.first().unwrap();
-            let mut buf = s.0.clone();
-            while buf.iter().any(|&n| n != 0) {
-                let old = buf.clone();
-                buf.clear();
-                for window in old.windows(2) {
-                    let &[a, b] = window else { panic!() };
-                    buf.push(a - b);
-                }
-                next += buf.first().unwrap();
-            }
-            next
-        })
-        .sum()
+pub fn part2(input: &[Sequence]) -> i128 {
+    input.iter().map(|s| poly::extrapolate(&s.0, -1)).sum()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn part1_example() {
-        let input = r#"0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45"#;
-        assert_eq!(part1(&parse(&input)), 114);
-    }
+impl FromStr for Sequence {
+    type Err = ParseError;
 
-    #[test]
-    fn part2_example() {
-        let input = r#"0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45"#;
-        assert_eq!(part2(&parse(&input)), 2);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let src = Source::new(9, s);
+        src.ints(s, " ", "number")
+            .collect::<Result<_, _>>()
+            .map(Sequence)
     }
 }
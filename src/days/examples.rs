@@ -0,0 +1,370 @@
+//! Puzzle examples with their expected answers.
+//!
+//! Every row becomes a test running all registered solutions for that day and part,
+//! so an extra edge case only needs a new line in the table.
+
+use super::registry;
+use crate::answer::Answer;
+
+macro_rules! examples {
+    ($($name:ident: $day:literal, $part:literal => $input:expr => $answer:literal,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check($day, $part, $input, stringify!($answer));
+            }
+        )*
+    };
+}
+
+fn check(day: u8, part: u8, input: &str, expected: &str) {
+    let expected = expected.parse::<Answer>().unwrap();
+    let solutions = registry()
+        .iter()
+        .filter(|s| s.day == day && s.part == part)
+        .collect::<Vec<_>>();
+    assert!(
+        !solutions.is_empty(),
+        "day {day} part {part} is not registered"
+    );
+    for solution in solutions {
+        match (solution.run)(input) {
+            Ok(answer) => assert_eq!(
+                answer, expected,
+                "day {day} part {part} ({})",
+                solution.name
+            ),
+            Err(e) => panic!("day {day} part {part} ({}): {e}", solution.name),
+        }
+    }
+}
+
+examples! {
+    day1_part1: 1, 1 => example!(1abc2 pqr3stu8vwx a1b2c3d4e5f treb7uchet) => 142,
+    day1_part2: 1, 2 => example!(two1nine eightwothree abcone2threexyz xtwone3four 4nineeightseven2 zoneight234 7pqrstsixteen) => 281,
+    day1_part2_long: 1, 2 => example!(xgkfonethreexnlcptbgxhnine4fivetwosix) => 16,
+    day1_part2_sevenine: 1, 2 => example!(sevenine) => 79,
+    day1_part2_oneight: 1, 2 => example!(oneight) => 18,
+    day1_part2_threeeeight: 1, 2 => example!(threeeeight) => 38,
+    day1_part2_ninine: 1, 2 => example!(ninine2) => 92,
+    day2_part1: 2, 1 => DAY2 => 8,
+    day2_part2: 2, 2 => DAY2 => 2286,
+    day3_part1: 3, 1 => DAY3 => 4361,
+    day3_part2: 3, 2 => DAY3 => 467835,
+    day4_part1: 4, 1 => DAY4 => 13,
+    day4_part2: 4, 2 => DAY4 => 30,
+    day5_part1: 5, 1 => DAY5 => 35,
+    day5_part2: 5, 2 => DAY5 => 46,
+    day6_part1: 6, 1 => DAY6 => 288,
+    day6_part2: 6, 2 => DAY6 => 71503,
+    day7_part1: 7, 1 => DAY7 => 6440,
+    day7_part2: 7, 2 => DAY7 => 5905,
+    day8_part1_1: 8, 1 => DAY8_1 => 2,
+    day8_part1_2: 8, 1 => DAY8_2 => 6,
+    day8_part2: 8, 2 => DAY8_PART2 => 6,
+    day9_part1: 9, 1 => DAY9 => 114,
+    day9_part2: 9, 2 => DAY9 => 2,
+    day10_part1_1: 10, 1 => DAY10_1 => 4,
+    day10_part1_2: 10, 1 => DAY10_2 => 8,
+    day10_part2: 10, 2 => DAY10_PART2 => 4,
+    day11_part1: 11, 1 => DAY11 => 374,
+    day12_part1: 12, 1 => DAY12 => 21,
+    day12_part2: 12, 2 => DAY12 => 525152,
+    day13_part1: 13, 1 => DAY13 => 405,
+    day13_part2: 13, 2 => DAY13 => 400,
+    day14_part1: 14, 1 => DAY14 => 136,
+    day14_part2: 14, 2 => DAY14 => 64,
+    day15_part1: 15, 1 => DAY15 => 1320,
+    day15_part2: 15, 2 => DAY15 => 145,
+    day16_part1: 16, 1 => DAY16 => 46,
+    day16_part2: 16, 2 => DAY16 => 51,
+    day17_part1: 17, 1 => DAY17 => 102,
+    day17_part2: 17, 2 => DAY17 => 94,
+    day18_part1: 18, 1 => DAY18 => 62,
+    day18_part2: 18, 2 => DAY18 => 952408144115,
+    day19_part1: 19, 1 => DAY19 => 19114,
+    day19_part2: 19, 2 => DAY19 => 167409079868000,
+    day20_part1: 20, 1 => DAY20 => 32000000,
+    day22_part1: 22, 1 => DAY22 => 5,
+    day22_part2: 22, 2 => DAY22 => 7,
+    day23_part1: 23, 1 => DAY23 => 94,
+    day23_part2: 23, 2 => DAY23 => 154,
+    day25_part1: 25, 1 => DAY25 => 54,
+}
+
+const DAY2: &str = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"#;
+
+const DAY3: &str = r#"467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598.."#;
+
+const DAY4: &str = r#"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#;
+
+const DAY5: &str = r#"seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4"#;
+
+const DAY6: &str = r#"Time:      7  15   30
+Distance:  9  40  200"#;
+
+const DAY7: &str = r#"32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483"#;
+
+const DAY8_1: &str = r#"RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)"#;
+
+const DAY8_2: &str = r#"LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)"#;
+
+const DAY8_PART2: &str = r#"LRLR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)"#;
+
+const DAY9: &str = r#"0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45"#;
+
+const DAY10_1: &str = r#".....
+.S-7.
+.|.|.
+.L-J.
+....."#;
+
+const DAY10_2: &str = r#"..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ..."#;
+
+const DAY10_PART2: &str = r#"...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."#;
+
+const DAY11: &str = r#"...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#....."#;
+
+const DAY12: &str = r#"???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1"#;
+
+const DAY13: &str = r#"#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#"#;
+
+const DAY14: &str = r#"O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#...."#;
+
+const DAY15: &str = r#"rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7"#;
+
+const DAY16: &str = r#".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|...."#;
+
+const DAY17: &str = r#"2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533"#;
+
+const DAY18: &str = r#"R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)"#;
+
+const DAY19: &str = r#"px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}"#;
+
+const DAY20: &str = r#"broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a"#;
+
+const DAY22: &str = r#"1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9"#;
+
+const DAY23: &str = r#"#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#"#;
+
+const DAY25: &str = r#"jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr"#;
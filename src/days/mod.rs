@@ -23,3 +23,183 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+#[cfg(test)]
+mod examples;
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    answer::{Answer, IntoAnswer},
+    error::{Error, ParseError},
+};
+
+/// Cube game analytics beyond the two answers, with arbitrary colours and bag limits.
+pub use day2::{Bag, Game, Record};
+/// Engine schematic adjacency between part numbers and symbols.
+pub use day3::{Diagram, Neighborhood, Number, Schematic, Symbol};
+/// Scratchcard copies won by each card, whatever the card ids.
+pub use day4::{Card, Cascade, DuplicateId};
+
+/// A registered solution for one part of a day.
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    pub day: u8,
+    pub part: u8,
+    /// Name of the solver function, e.g. `part2_brute`.
+    pub name: &'static str,
+    /// Runs the day's generator followed by the solver.
+    pub run: fn(&str) -> Result<Answer, Error>,
+    /// Same as `run`, also measuring the generator and the solver separately.
+    pub timed: fn(&str) -> Result<(Answer, Timing), Error>,
+}
+
+/// Time spent in each phase of a single run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    pub generator: Duration,
+    pub solver: Duration,
+}
+
+impl Timing {
+    pub fn total(&self) -> Duration {
+        self.generator + self.solver
+    }
+}
+
+macro_rules! registry {
+    ($($day:literal, $part:literal => $gen:path => $module:ident::$solver:ident),* $(,)?) => {
+        static REGISTRY: &[Solution] = &[$(
+            Solution {
+                day: $day,
+                part: $part,
+                name: stringify!($solver),
+                run: |input| {
+                    let parsed = $gen(input)?;
+                    $module::$solver(&parsed).into_answer()
+                },
+                timed: |input| {
+                    let start = Instant::now();
+                    let parsed = $gen(input)?;
+                    let generator = start.elapsed();
+                    let start = Instant::now();
+                    let answer = $module::$solver(&parsed).into_answer()?;
+                    let solver = start.elapsed();
+                    Ok((answer, Timing { generator, solver }))
+                },
+            },
+        )*];
+    };
+}
+
+/// Every registered solution, ordered by day and part.
+///
+/// A part may have several named variants, e.g. a reference implementation next to an
+/// optimized one; the first one registered is the default.
+pub fn registry() -> &'static [Solution] {
+    REGISTRY
+}
+
+/// Looks up the default solution for a given day and part, i.e. the first registered one.
+pub fn find(day: u8, part: u8) -> Option<&'static Solution> {
+    variants(day, part).next()
+}
+
+/// All registered implementations of a given day and part, default first.
+pub fn variants(day: u8, part: u8) -> impl Iterator<Item = &'static Solution> {
+    REGISTRY
+        .iter()
+        .filter(move |s| s.day == day && s.part == part)
+}
+
+/// Registered solutions grouped by day and part, each group starting with the default.
+pub fn parts() -> impl Iterator<Item = &'static [Solution]> {
+    REGISTRY.chunk_by(|a, b| (a.day, a.part) == (b.day, b.part))
+}
+
+// Days without a generator work on the raw input.
+fn raw(input: &str) -> Result<&str, ParseError> {
+    Ok(input)
+}
+
+registry! {
+    1, 1 => raw => day1::part1,
+    1, 1 => raw => day1::part1_lines,
+    1, 2 => raw => day1::part2,
+    1, 2 => raw => day1::part2_lines,
+    2, 1 => day2::parse => day2::part1,
+    2, 2 => day2::parse => day2::part2,
+    3, 1 => day3::parse => day3::part1,
+    3, 2 => day3::parse => day3::part2,
+    4, 1 => day4::parse => day4::part1,
+    4, 2 => day4::parse => day4::part2,
+    5, 1 => day5::parse => day5::part1,
+    5, 2 => day5::parse => day5::part2,
+    6, 1 => day6::parse => day6::part1,
+    6, 2 => day6::parse => day6::part2,
+    7, 1 => day7::parse => day7::part1,
+    7, 2 => day7::parse => day7::part2,
+    8, 1 => day8::parse => day8::part1,
+    8, 2 => day8::parse => day8::part2,
+    9, 1 => day9::parse => day9::part1,
+    9, 2 => day9::parse => day9::part2,
+    10, 1 => day10::parse => day10::part1,
+    10, 2 => day10::parse => day10::part2,
+    11, 1 => day11::parse => day11::part1,
+    11, 2 => day11::parse => day11::part2,
+    12, 1 => day12::parse => day12::part1,
+    12, 2 => day12::parse => day12::part2,
+    13, 1 => day13::parse => day13::part1,
+    13, 2 => day13::parse => day13::part2,
+    14, 1 => day14::parse => day14::part1,
+    14, 2 => day14::parse => day14::part2,
+    15, 1 => raw => day15::part1,
+    15, 2 => day15::parse => day15::part2,
+    16, 1 => day16::parse => day16::part1,
+    16, 2 => day16::parse => day16::part2,
+    16, 2 => day16::parse => day16::part2_brute,
+    17, 1 => day17::parse => day17::part1,
+    17, 2 => day17::parse => day17::part2,
+    18, 1 => day18::parse => day18::part1,
+    18, 2 => day18::parse => day18::part2,
+    19, 1 => day19::parse => day19::part1,
+    19, 2 => day19::parse => day19::part2,
+    20, 1 => day20::parse => day20::part1,
+    20, 2 => day20::parse => day20::part2,
+    21, 1 => day21::parse => day21::part1,
+    21, 2 => day21::parse => day21::part2,
+    22, 1 => day22::parse => day22::part1,
+    22, 2 => day22::parse => day22::part2,
+    23, 1 => day23::parse => day23::part1,
+    23, 2 => day23::parse => day23::part2,
+    24, 1 => day24::parse => day24::part1,
+    24, 2 => day24::parse => day24::part2,
+    25, 1 => day25::parse => day25::part1,
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn registry_is_ordered() {
+        let keys = registry()
+            .iter()
+            .map(|s| (s.day, s.part, s.name))
+            .collect::<Vec<_>>();
+        assert!(keys
+            .windows(2)
+            .all(|w| (w[0].0, w[0].1) <= (w[1].0, w[1].1)));
+        assert!(keys.iter().all_unique());
+        assert_eq!(keys.iter().map(|k| (k.0, k.1)).dedup().count(), 49);
+        assert_eq!(find(16, 2).map(|s| s.name), Some("part2"));
+        assert_eq!(variants(16, 2).count(), 2);
+    }
+
+    #[test]
+    fn parse_errors_are_reported() {
+        let err = (find(2, 1).unwrap().run)("Game 1: 3 blu").unwrap_err();
+        assert!(matches!(err, Error::Parse(e) if e.found() == "blu"));
+    }
+}
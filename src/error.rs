@@ -0,0 +1,368 @@
+use std::{borrow::Cow, error, fmt, io, str::FromStr};
+
+use crate::util::{
+    grid::Grid,
+    parse::{self, Int, ScanError},
+};
+
+/// Malformed puzzle input, pointing at the offending line and column.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseError {
+    day: u8,
+    line: usize,
+    column: usize,
+    offset: usize,
+    expected: Cow<'static, str>,
+    found: String,
+    source_line: String,
+}
+
+/// Failure to solve a puzzle part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    /// Input that parses but that the solver can't make sense of, e.g. while streaming it.
+    Solve(String),
+}
+
+/// Input text being parsed for a given day.
+///
+/// Errors are located by pointer offset, so every `&str` handed to these methods
+/// must be a slice of the text the source was created with.
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    day: u8,
+    text: &'a str,
+}
+
+impl ParseError {
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// 1-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column, in characters.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+}
+
+impl<'a> Source<'a> {
+    pub fn new(day: u8, text: &'a str) -> Self {
+        Self { day, text }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'a> {
+        self.text.lines()
+    }
+
+    /// Builds an error pointing at `at`, which should be the unexpected token.
+    /// An empty slice reports the end of the line.
+    pub fn error(&self, at: &str, expected: impl Into<Cow<'static, str>>) -> ParseError {
+        let offset = self.offset_of(at);
+        let found = at.lines().next().unwrap_or_default();
+        self.error_at(offset, expected.into(), found)
+    }
+
+    pub fn parse<T: FromStr>(&self, at: &str, expected: &'static str) -> Result<T, ParseError> {
+        at.parse().map_err(|_| self.error(at, expected))
+    }
+
+    pub fn split_once(
+        &self,
+        at: &'a str,
+        sep: &'static str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        at.split_once(sep)
+            .ok_or_else(|| self.error(&at[at.len()..], format!("{sep:?}")))
+    }
+
+    pub fn strip_prefix(&self, at: &'a str, prefix: &'static str) -> Result<&'a str, ParseError> {
+        at.strip_prefix(prefix)
+            .ok_or_else(|| self.error(at, format!("{prefix:?}")))
+    }
+
+    pub fn strip_suffix(&self, at: &'a str, suffix: &'static str) -> Result<&'a str, ParseError> {
+        at.strip_suffix(suffix)
+            .ok_or_else(|| self.error(&at[at.len()..], format!("{suffix:?}")))
+    }
+
+    /// Collects exactly `N` items out of `parts`, which were split from `at`.
+    pub fn array<const N: usize>(
+        &self,
+        at: &'a str,
+        parts: impl IntoIterator<Item = &'a str>,
+        expected: &'static str,
+    ) -> Result<[&'a str; N], ParseError> {
+        let mut res = [""; N];
+        let mut parts = parts.into_iter();
+        for slot in &mut res {
+            *slot = parts
+                .next()
+                .ok_or_else(|| self.error(&at[at.len()..], expected))?;
+        }
+        match parts.next() {
+            Some(extra) => Err(self.error(extra, "end of line")),
+            None => Ok(res),
+        }
+    }
+
+    /// Every integer in `at`, only separated by characters of `separators`, with overflowing
+    /// ones and anything else in between reported as errors.
+    pub fn ints<T: Int>(
+        &self,
+        at: &'a str,
+        separators: &'a str,
+        expected: &'static str,
+    ) -> impl Iterator<Item = Result<T, ParseError>> + 'a {
+        let src = *self;
+        parse::ints(at, separators).map(move |n| n.map_err(|err| src.scan_error(at, err, expected)))
+    }
+
+    /// Exactly `N` integers out of `at`, e.g. the coordinates on a line. See [`Source::ints`].
+    pub fn ints_n<T: Int, const N: usize>(
+        &self,
+        at: &'a str,
+        separators: &'a str,
+        expected: &'static str,
+    ) -> Result<[T; N], ParseError> {
+        parse::ints_n(at, separators).map_err(|err| self.scan_error(at, err, expected))
+    }
+
+    /// Parses a character map, one row per line.
+    pub fn grid<T>(
+        &self,
+        expected: &'static str,
+        mut tile: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(self.text.len());
+        for line in self.text.lines() {
+            let mut row_len = 0;
+            for (i, c) in line.char_indices() {
+                let t = tile(c).ok_or_else(|| self.error(&line[i..i + c.len_utf8()], expected))?;
+                cells.push(t);
+                row_len += 1;
+            }
+            if *width.get_or_insert(row_len) != row_len {
+                return Err(self.error(
+                    &line[line.len()..],
+                    format!("row of width {}", width.unwrap()),
+                ));
+            }
+            height += 1;
+        }
+        Ok(Grid::new(width.unwrap_or_default(), height, cells))
+    }
+
+    /// Re-anchors an error produced while parsing the sub-slice `at` on its own.
+    pub fn rebase(&self, at: &str, err: ParseError) -> ParseError {
+        let offset = self.offset_of(at) + err.offset;
+        let found = err.found;
+        self.error_at(offset, err.expected, &found)
+    }
+
+    fn offset_of(&self, at: &str) -> usize {
+        let offset = (at.as_ptr() as usize).wrapping_sub(self.text.as_ptr() as usize);
+        if offset > self.text.len() {
+            0
+        } else {
+            offset
+        }
+    }
+
+    fn scan_error(&self, at: &str, err: ScanError<'_>, expected: &'static str) -> ParseError {
+        match err {
+            ScanError::Overflow(text) | ScanError::Separator(text) => self.error(text, expected),
+            ScanError::Missing => self.error(&at[at.len()..], expected),
+            ScanError::Extra(digits) => self.error(digits, "end of line"),
+        }
+    }
+
+    fn error_at(&self, offset: usize, expected: Cow<'static, str>, found: &str) -> ParseError {
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            day: self.day,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            offset,
+            expected,
+            found: found.to_string(),
+            source_line: self.text[line_start..]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+/// Parses every line of `input` with `T`'s [`FromStr`] implementation.
+pub fn parse_lines<T: FromStr<Err = ParseError>>(input: &str) -> Result<Vec<T>, ParseError> {
+    parse_all(input, input.lines())
+}
+
+/// Parses every item of `parts`, which were split from `input`.
+pub fn parse_all<'a, T: FromStr<Err = ParseError>>(
+    input: &'a str,
+    parts: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<T>, ParseError> {
+    parts
+        .into_iter()
+        .map(|part| {
+            part.parse().map_err(|err: ParseError| {
+                let day = err.day;
+                Source::new(day, input).rebase(part, err)
+            })
+        })
+        .collect()
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let found = if self.found.is_empty() {
+            "end of line".to_string()
+        } else {
+            format!("{:?}", self.found)
+        };
+        writeln!(
+            f,
+            "day {}, line {}, column {}: expected {}, found {found}",
+            self.day, self.line, self.column, self.expected
+        )?;
+        let gutter = self.line.to_string().len();
+        writeln!(f, "{:>gutter$} | {}", self.line, self.source_line)?;
+        write!(
+            f,
+            "{:>gutter$} | {:>col$}{}",
+            "",
+            "",
+            "^".repeat(self.found.chars().count().max(1)),
+            col = self.column - 1,
+        )
+    }
+}
+
+// cargo-aoc reports generator failures with `{:#?}`, so render the diagnostic there too.
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Solve(err.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "invalid input: {err}"),
+            Self::Solve(msg) => write!(f, "no solution: {msg}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Parse(err) => Some(err),
+            Self::Solve(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location() {
+        let text = "Game 1: 3 blue\nGame 2: x red";
+        let src = Source::new(2, text);
+        let line = text.lines().nth(1).unwrap();
+        let err = src.parse::<u32>(&line[8..9], "number").unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 9));
+        assert_eq!(err.found(), "x");
+        assert_eq!(
+            err.to_string(),
+            "day 2, line 2, column 9: expected number, found \"x\"\n\
+             2 | Game 2: x red\n  \
+               |         ^"
+        );
+    }
+
+    #[test]
+    fn rebase() {
+        let text = "ok\nbad,ok";
+        let part = &text[3..];
+        let inner = Source::new(1, part).error(&part[..3], "digit");
+        let err = Source::new(1, text).rebase(part, inner);
+        assert_eq!((err.line(), err.column()), (2, 1));
+        assert_eq!(err.found(), "bad");
+    }
+
+    #[test]
+    fn missing_separator() {
+        let src = Source::new(4, "Card 1");
+        let err = src.split_once(src.text(), ": ").unwrap_err();
+        assert_eq!((err.line(), err.column(), err.found()), (1, 7, ""));
+        assert!(err
+            .to_string()
+            .ends_with("found end of line\n1 | Card 1\n  |       ^"));
+    }
+
+    #[test]
+    fn int_separators() {
+        let src = Source::new(12, "???.### 1,1;3");
+        let key = &src.text()[8..];
+        let err = src
+            .ints::<u32>(key, ",", "group size")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert_eq!((err.line(), err.column(), err.found()), (1, 12, ";"));
+        assert_eq!(err.expected(), "group size");
+
+        let src = Source::new(22, "1,0,1~2,0,1 5");
+        let err = src
+            .ints_n::<u32, 6>(src.text(), ",~", "x,y,z~x,y,z")
+            .unwrap_err();
+        assert_eq!((err.column(), err.found()), (12, " "));
+    }
+
+    #[test]
+    fn ragged_grid() {
+        let src = Source::new(10, "..\n...");
+        let err = src.grid("tile", |c| (c == '.').then_some(())).unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 4));
+        assert_eq!(err.expected(), "row of width 2");
+    }
+}
@@ -1,11 +1,15 @@
-#[allow(unused_macros)]
+#[cfg(test)]
 macro_rules! example {
     ($($values:expr) +) => {
         &stringify!($($values)*).replace(" ", "\n")
     };
 }
 
+pub mod answer;
+pub mod answers;
+pub mod bench;
 pub mod days;
+pub mod error;
 pub mod util;
 
 #[macro_use]
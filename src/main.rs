@@ -1,4 +1,386 @@
-extern crate aoc_2023;
-use aoc_runner_derive::aoc_main;
+use std::{
+    env,
+    fmt::Debug,
+    fs,
+    io::{self, Read},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    time::Instant,
+};
 
-aoc_main! { lib = aoc_2023 }
+use aoc_2023::{
+    answers::{self, Answers, Outcome},
+    bench::{self, Options, Report},
+    days,
+};
+
+const USAGE: &str = "\
+Usage: aoc_2023 run --day <N> [--part <P>] [--input <PATH|->] [--variants]
+       aoc_2023 run --all [--variants]
+       aoc_2023 bench-all [--iterations <N>] [--warmup <N>] [--json] [--variants]
+       aoc_2023 record [--day <N>] [--answers <PATH>]
+       aoc_2023 verify [--day <N>] [--answers <PATH>]
+
+Without --input, inputs are read from input/2023/day<N>.txt (the cargo-aoc layout).
+Passing `-` as the input path reads from stdin.
+--variants runs every registered implementation of a part instead of the default one,
+checking that they agree.
+`record` stores the current answers in answers.toml, `verify` checks them.";
+
+enum Command {
+    Run {
+        day: Option<u8>,
+        part: Option<u8>,
+        input: Option<InputSource>,
+        variants: bool,
+    },
+    BenchAll {
+        options: Options,
+        json: bool,
+        variants: bool,
+    },
+    Answers {
+        record: bool,
+        day: Option<u8>,
+        path: PathBuf,
+    },
+}
+
+enum InputSource {
+    Stdin,
+    Path(PathBuf),
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(msg) => {
+            eprintln!("{msg}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let ok = match command {
+        Command::Run {
+            day,
+            part,
+            input,
+            variants,
+        } => run(day, part, input, variants),
+        Command::BenchAll {
+            options,
+            json,
+            variants,
+        } => bench_all(options, json, variants),
+        Command::Answers { record, day, path } => check_answers(record, day, &path),
+    };
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let Some((command, args)) = args.split_first() else {
+        return Err("missing command".to_string());
+    };
+    match command.as_str() {
+        "run" => parse_run(args),
+        "bench-all" => parse_bench_all(args),
+        "record" => parse_answers(true, args),
+        "verify" => parse_answers(false, args),
+        _ => Err(format!("unknown command {command:?}")),
+    }
+}
+
+fn parse_run(args: &[String]) -> Result<Command, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut all = false;
+    let mut variants = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--day" => day = Some(parse_number(value()?, 1..=25)?),
+            "--part" => part = Some(parse_number(value()?, 1..=2)?),
+            "--input" => {
+                input = Some(match value()?.as_str() {
+                    "-" => InputSource::Stdin,
+                    path => InputSource::Path(path.into()),
+                })
+            }
+            "--all" => all = true,
+            "--variants" => variants = true,
+            arg => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+
+    match (day, all) {
+        (Some(_), true) => Err("--day and --all are mutually exclusive".to_string()),
+        (None, false) => Err("either --day or --all is required".to_string()),
+        (None, true) if part.is_some() || input.is_some() => {
+            Err("--all can't be combined with --part or --input".to_string())
+        }
+        _ => Ok(Command::Run {
+            day,
+            part,
+            input,
+            variants,
+        }),
+    }
+}
+
+fn parse_bench_all(args: &[String]) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut json = false;
+    let mut variants = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--iterations" => options.iterations = parse_number(value()?, 1..=u32::MAX)?,
+            "--warmup" => options.warmup = parse_number(value()?, 0..=u32::MAX)?,
+            "--json" => json = true,
+            "--variants" => variants = true,
+            arg => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+
+    Ok(Command::BenchAll {
+        options,
+        json,
+        variants,
+    })
+}
+
+fn parse_answers(record: bool, args: &[String]) -> Result<Command, String> {
+    let mut day = None;
+    let mut path = PathBuf::from(answers::DEFAULT_PATH);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--day" => day = Some(parse_number(value()?, 1..=25)?),
+            "--answers" => path = value()?.into(),
+            arg => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+
+    Ok(Command::Answers { record, day, path })
+}
+
+fn parse_number<T: FromStr + PartialOrd + Debug>(
+    value: &str,
+    range: RangeInclusive<T>,
+) -> Result<T, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|n| range.contains(n))
+        .ok_or_else(|| format!("expected a number in {range:?}, found {value:?}"))
+}
+
+fn read_input(day: u8, source: Option<&InputSource>) -> io::Result<String> {
+    let mut input = match source {
+        Some(InputSource::Stdin) => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+        Some(InputSource::Path(path)) => fs::read_to_string(path)?,
+        None => fs::read_to_string(format!("input/2023/day{day}.txt"))?,
+    };
+    // Same normalization as cargo-aoc
+    input.truncate(input.trim_end_matches('\n').len());
+    Ok(input)
+}
+
+fn run(day: Option<u8>, part: Option<u8>, source: Option<InputSource>, variants: bool) -> bool {
+    let mut ok = true;
+
+    for d in 1..=25 {
+        if day.is_some_and(|day| day != d) {
+            continue;
+        }
+        let parts = days::parts()
+            .filter(|s| s[0].day == d && part.is_none_or(|part| part == s[0].part))
+            .collect::<Vec<_>>();
+        if parts.is_empty() {
+            eprintln!("Day {d}: no solution for part {}", part.unwrap_or(1));
+            ok = false;
+            continue;
+        }
+
+        let input = match read_input(d, source.as_ref()) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {d}: failed to read input: {e}");
+                ok = false;
+                continue;
+            }
+        };
+
+        for solutions in parts {
+            let p = solutions[0].part;
+            let solutions = if variants { solutions } else { &solutions[..1] };
+            let mut answers = Vec::new();
+            for solution in solutions {
+                let name = if variants {
+                    format!(" [{}]", solution.name)
+                } else {
+                    String::new()
+                };
+                let start = Instant::now();
+                match (solution.run)(&input) {
+                    Ok(answer) => {
+                        println!("Day {d} - Part {p}{name}: {answer} ({:?})", start.elapsed());
+                        answers.push(answer);
+                    }
+                    Err(e) => {
+                        eprintln!("Day {d} - Part {p}{name}: {e}");
+                        ok = false;
+                    }
+                }
+            }
+            if answers.windows(2).any(|w| w[0] != w[1]) {
+                eprintln!("Day {d} - Part {p}: variants disagree");
+                ok = false;
+            }
+        }
+    }
+
+    ok
+}
+
+fn bench_all(options: Options, json: bool, variants: bool) -> bool {
+    let mut ok = true;
+    let mut report = Report {
+        options,
+        parts: Vec::new(),
+        variants: Vec::new(),
+    };
+
+    for solutions in days::registry().chunk_by(|a, b| a.day == b.day) {
+        let d = solutions[0].day;
+        let input = match read_input(d, None) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {d}: failed to read input: {e}");
+                ok = false;
+                continue;
+            }
+        };
+        for solutions in solutions.chunk_by(|a, b| a.part == b.part) {
+            let solutions = if variants { solutions } else { &solutions[..1] };
+            for (i, solution) in solutions.iter().enumerate() {
+                match bench::bench(solution, &input, options) {
+                    // Only the default implementation counts against the budget
+                    Ok(timing) if i == 0 => report.parts.push(timing),
+                    Ok(timing) => report.variants.push(timing),
+                    Err(e) => {
+                        eprintln!("Day {d} - Part {}: {e}", solution.part);
+                        ok = false;
+                    }
+                }
+            }
+        }
+    }
+
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        println!("{report}");
+    }
+    ok
+}
+
+fn check_answers(record: bool, day: Option<u8>, path: &Path) -> bool {
+    let mut answers = match fs::read_to_string(path) {
+        Ok(text) => match Answers::parse(&text) {
+            Ok(answers) => answers,
+            Err(e) => {
+                eprintln!("{}: {e}", path.display());
+                return false;
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Answers::default(),
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return false;
+        }
+    };
+    if !record && answers.is_empty() {
+        eprintln!(
+            "{}: no recorded answers, run `record` first",
+            path.display()
+        );
+        return false;
+    }
+
+    let mut ok = true;
+    for solutions in days::registry().chunk_by(|a, b| a.day == b.day) {
+        let d = solutions[0].day;
+        if day.is_some_and(|day| day != d) {
+            continue;
+        }
+        let input = match read_input(d, None) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {d}: failed to read input: {e}");
+                ok = false;
+                continue;
+            }
+        };
+        for solutions in solutions.chunk_by(|a, b| a.part == b.part) {
+            // Only the default implementation is recorded, but every variant is verified
+            let solutions = if record { &solutions[..1] } else { solutions };
+            for solution in solutions {
+                let p = solution.part;
+                let name = if solutions.len() > 1 {
+                    format!(" [{}]", solution.name)
+                } else {
+                    String::new()
+                };
+                let outcome = answers::check(solution, &input, &answers);
+                match (record, outcome) {
+                    (true, Outcome::Correct) => println!("Day {d} - Part {p}: unchanged"),
+                    (true, Outcome::Mismatch { actual, .. } | Outcome::Missing(actual)) => {
+                        println!("Day {d} - Part {p}: recorded {actual}");
+                        answers.insert(d, p, actual);
+                    }
+                    (_, outcome) => {
+                        ok &= outcome == Outcome::Correct;
+                        println!("Day {d} - Part {p}{name}: {outcome}");
+                    }
+                }
+            }
+        }
+    }
+
+    if record {
+        if let Err(e) = fs::write(path, answers.to_string()) {
+            eprintln!("{}: {e}", path.display());
+            return false;
+        }
+    }
+    ok
+}
@@ -1,61 +1,429 @@
-use std::{iter::FromIterator, ops::BitAnd};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign},
+};
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Default, Debug)]
-pub struct BitSet(u64);
+/// Fixed-size set of small integers, stored as `WORDS` 64-bit words.
+///
+/// Inserting an index past [`BitSet::CAPACITY`] panics, use [`DynBitSet`] when the
+/// upper bound isn't known in advance.
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+pub struct BitSet<const WORDS: usize = 1>([u64; WORDS]);
+
+/// Growable set of small integers, backed by as many 64-bit words as needed.
+#[derive(Clone, Default)]
+pub struct DynBitSet(Vec<u64>);
+
+/// Iterator over the set bits of a bit set, in ascending order.
+#[derive(Clone)]
+pub struct Iter<'a> {
+    words: &'a [u64],
+    base: usize,
+    current: u64,
+}
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    pub const CAPACITY: usize = WORDS * 64;
+
+    pub const fn new() -> Self {
+        Self([0; WORDS])
+    }
 
-impl BitSet {
     pub fn reduce_sum(self) -> u32 {
         // We can take advantage of the fact that the last set only has
         // one element.
-        debug_assert_eq!(self.0.count_ones(), 1);
-        self.0.trailing_zeros()
+        debug_assert_eq!(self.len(), 1);
+        self.first().unwrap() as u32
+    }
+
+    pub fn len(&self) -> usize {
+        len(&self.0)
     }
 
-    pub fn len(self) -> u32 {
-        self.0.count_ones()
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
     }
 
-    pub fn is_empty(self) -> bool {
-        self.len() == 0
+    /// Returns whether the index wasn't already in the set.
+    pub fn insert(&mut self, index: usize) -> bool {
+        let (word, bit) = split(index);
+        let was_set = self.0[word] & bit != 0;
+        self.0[word] |= bit;
+        !was_set
     }
 
-    pub fn insert(&mut self, index: usize) {
-        self.0 |= 1 << index;
+    /// Returns whether the index was in the set.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let (word, bit) = split(index);
+        let was_set = self.0[word] & bit != 0;
+        self.0[word] &= !bit;
+        was_set
     }
 
     pub fn contains(&self, index: usize) -> bool {
-        self.0 & (1 << index) != 0
+        let (word, bit) = split(index);
+        self.0.get(word).is_some_and(|w| w & bit != 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = [0; WORDS];
+    }
+
+    /// Smallest index in the set.
+    pub fn first(&self) -> Option<usize> {
+        first(&self.0)
+    }
+
+    /// Largest index in the set.
+    pub fn last(&self) -> Option<usize> {
+        last(&self.0)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(&self.0)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & b == 0)
+    }
+}
+
+impl DynBitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty set that can hold indices below `capacity` without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity.div_ceil(64)))
+    }
+
+    pub fn len(&self) -> usize {
+        len(&self.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    /// Returns whether the index wasn't already in the set.
+    pub fn insert(&mut self, index: usize) -> bool {
+        let (word, bit) = split(index);
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        let was_set = self.0[word] & bit != 0;
+        self.0[word] |= bit;
+        !was_set
+    }
+
+    /// Returns whether the index was in the set.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let (word, bit) = split(index);
+        let Some(w) = self.0.get_mut(word) else {
+            return false;
+        };
+        let was_set = *w & bit != 0;
+        *w &= !bit;
+        was_set
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        let (word, bit) = split(index);
+        self.0.get(word).is_some_and(|w| w & bit != 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Smallest index in the set.
+    pub fn first(&self) -> Option<usize> {
+        first(&self.0)
+    }
+
+    /// Largest index in the set.
+    pub fn last(&self) -> Option<usize> {
+        last(&self.0)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(&self.0)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a & !other.0.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & b == 0)
+    }
+
+    /// Words up to the last non-zero one, so that equal sets compare and hash the same
+    /// regardless of how far they once grew.
+    fn trimmed(&self) -> &[u64] {
+        let len = self.0.iter().rposition(|&w| w != 0).map_or(0, |i| i + 1);
+        &self.0[..len]
+    }
+
+    fn zip_with(&mut self, rhs: &Self, f: impl Fn(u64, u64) -> u64) {
+        if self.0.len() < rhs.0.len() {
+            self.0.resize(rhs.0.len(), 0);
+        }
+        for (i, w) in self.0.iter_mut().enumerate() {
+            *w = f(*w, rhs.0.get(i).copied().unwrap_or(0));
+        }
+    }
+}
+
+fn split(index: usize) -> (usize, u64) {
+    (index / 64, 1 << (index % 64))
+}
+
+fn len(words: &[u64]) -> usize {
+    words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn first(words: &[u64]) -> Option<usize> {
+    let i = words.iter().position(|&w| w != 0)?;
+    Some(i * 64 + words[i].trailing_zeros() as usize)
+}
+
+fn last(words: &[u64]) -> Option<usize> {
+    let i = words.iter().rposition(|&w| w != 0)?;
+    Some(i * 64 + 63 - words[i].leading_zeros() as usize)
+}
+
+impl<'a> Iter<'a> {
+    fn new(words: &'a [u64]) -> Self {
+        Self {
+            words,
+            base: 0,
+            current: words.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.words = self.words.get(1..).filter(|w| !w.is_empty())?;
+            self.base += 64;
+            self.current = self.words[0];
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.current &= self.current - 1;
+        Some(self.base + bit)
+    }
+}
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl From<BitSet> for u64 {
     fn from(value: BitSet) -> Self {
-        value.0
+        value.0[0]
     }
 }
 
-impl FromIterator<u32> for BitSet {
-    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
-        let mut bits = 0;
-        for byte in iter {
-            debug_assert!(byte < 64, "{byte} does not fit in 64-bit set");
-            bits |= 1 << byte as usize;
+impl<'a, const WORDS: usize> IntoIterator for &'a BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a DynBitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = Self::new();
+        for index in iter {
+            debug_assert!(
+                index < Self::CAPACITY,
+                "{index} does not fit in {}-bit set",
+                Self::CAPACITY
+            );
+            set.insert(index);
         }
-        Self(bits)
+        set
+    }
+}
+
+impl<const WORDS: usize> FromIterator<u32> for BitSet<WORDS> {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        Self::from_iter(iter.into_iter().map(|n| n as usize))
     }
 }
 
-impl FromIterator<u8> for BitSet {
+impl<const WORDS: usize> FromIterator<u8> for BitSet<WORDS> {
     #[inline]
     fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
-        Self::from_iter(iter.into_iter().map(|n| n as u32))
+        Self::from_iter(iter.into_iter().map(|n| n as usize))
     }
 }
 
-impl BitAnd for BitSet {
-    type Output = Self;
+impl FromIterator<usize> for DynBitSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = Self::new();
+        for index in iter {
+            set.insert(index);
+        }
+        set
+    }
+}
+
+macro_rules! set_ops {
+    ($($op:ident::$method:ident, $assign:ident::$assign_method:ident => |$a:ident, $b:ident| $f:expr;)*) => {$(
+        impl<const WORDS: usize> $assign for BitSet<WORDS> {
+            fn $assign_method(&mut self, rhs: Self) {
+                for ($a, $b) in self.0.iter_mut().zip(rhs.0) {
+                    *$a = {
+                        let $a = *$a;
+                        $f
+                    };
+                }
+            }
+        }
+
+        impl<const WORDS: usize> $op for BitSet<WORDS> {
+            type Output = Self;
+
+            fn $method(mut self, rhs: Self) -> Self::Output {
+                self.$assign_method(rhs);
+                self
+            }
+        }
+
+        impl $assign<&DynBitSet> for DynBitSet {
+            fn $assign_method(&mut self, rhs: &DynBitSet) {
+                self.zip_with(rhs, |$a, $b| $f);
+            }
+        }
+
+        impl $op for &DynBitSet {
+            type Output = DynBitSet;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                let mut res = self.clone();
+                res.$assign_method(rhs);
+                res
+            }
+        }
+    )*};
+}
+
+set_ops! {
+    BitAnd::bitand, BitAndAssign::bitand_assign => |a, b| a & b;
+    BitOr::bitor, BitOrAssign::bitor_assign => |a, b| a | b;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => |a, b| a ^ b;
+    Sub::sub, SubAssign::sub_assign => |a, b| a & !b;
+}
+
+impl PartialEq for DynBitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl Eq for DynBitSet {}
+
+impl Hash for DynBitSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed().hash(state);
+    }
+}
+
+impl<const WORDS: usize> fmt::Debug for BitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl fmt::Debug for DynBitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed() {
+        let mut set = BitSet::<2>::new();
+        assert!(set.insert(3));
+        assert!(set.insert(100));
+        assert!(!set.insert(3));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [3, 100]);
+        assert_eq!(
+            (set.first(), set.last(), set.len()),
+            (Some(3), Some(100), 2)
+        );
+        assert!(set.remove(3));
+        assert!(!set.contains(3) && set.contains(100));
+        assert!(!set.contains(1000));
+        assert_eq!(BitSet::<1>::from_iter([5u8]).reduce_sum(), 5);
+    }
+
+    #[test]
+    fn algebra() {
+        let a = BitSet::<2>::from_iter([1usize, 2, 70]);
+        let b = BitSet::<2>::from_iter([2usize, 71]);
+        assert_eq!((a | b).iter().collect::<Vec<_>>(), [1, 2, 70, 71]);
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), [2]);
+        assert_eq!((a - b).iter().collect::<Vec<_>>(), [1, 70]);
+        assert_eq!((a ^ b).iter().collect::<Vec<_>>(), [1, 70, 71]);
+        assert!((a & b).is_subset(&a) && a.is_superset(&(a & b)));
+        assert!((a - b).is_disjoint(&b));
+    }
 
-    fn bitand(self, rhs: Self) -> Self::Output {
-        Self(self.0 & rhs.0)
+    #[test]
+    fn growable() {
+        let mut a = DynBitSet::new();
+        a.insert(500);
+        a.insert(1);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [1, 500]);
+        assert_eq!(a.last(), Some(500));
+        let b = DynBitSet::from_iter([1]);
+        assert!(b.is_subset(&a) && !a.is_subset(&b));
+        assert_eq!(&a - &b, DynBitSet::from_iter([500]));
+        a.remove(500);
+        // Trailing empty words don't matter
+        assert_eq!(a, b);
+        assert!(DynBitSet::new().iter().next().is_none());
     }
 }
@@ -0,0 +1,189 @@
+use std::hash::Hash;
+
+use fxhash::FxHashMap;
+use itertools::Itertools;
+
+use super::num;
+
+/// Shape of the sequence `x0, step(x0), step(step(x0)), ...` over a finite state space:
+/// states from index `mu` on repeat every `lambda` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub mu: usize,
+    pub lambda: usize,
+}
+
+/// Indices of the states at which some event happens, along with the cycle they follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub cycle: Cycle,
+    /// Every index below `mu + lambda` at which the event happens, sorted.
+    pub hits: Vec<usize>,
+}
+
+impl Cycle {
+    /// Index of the first state equal to the one at index `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.mu {
+            n
+        } else {
+            self.mu + (n - self.mu) % self.lambda
+        }
+    }
+}
+
+impl Schedule {
+    pub fn hits_at(&self, n: usize) -> bool {
+        self.hits.binary_search(&self.cycle.reduce(n)).is_ok()
+    }
+}
+
+/// Brent's algorithm: finds the cycle keeping only two states around, at the cost of
+/// stepping through it about three times.
+pub fn brent<S: Clone + Eq>(initial: S, mut step: impl FnMut(S) -> S) -> Cycle {
+    // Find lambda, looking for the tortoise among the hare's next power of two states
+    let mut power = 1;
+    let mut lambda = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial.clone());
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare.clone();
+            power *= 2;
+            lambda = 0;
+        }
+        hare = step(hare);
+        lambda += 1;
+    }
+
+    // Then mu, walking with both lambda steps apart until they meet
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..lambda {
+        hare = step(hare);
+    }
+    let mut mu = 0;
+    while tortoise != hare {
+        tortoise = step(tortoise);
+        hare = step(hare);
+        mu += 1;
+    }
+    Cycle { mu, lambda }
+}
+
+/// Finds the cycle in a single pass, remembering a `fingerprint` of every state rather than the
+/// states themselves. Fingerprints must be unique to each state, e.g. the part of it that matters.
+///
+/// Also returns the state at index `mu + lambda`, where the detection stopped.
+pub fn fingerprint<S, K: Hash + Eq>(
+    initial: S,
+    mut step: impl FnMut(S) -> S,
+    mut fingerprint: impl FnMut(&S) -> K,
+) -> (Cycle, S) {
+    let mut seen = FxHashMap::default();
+    let mut state = initial;
+    for i in 0.. {
+        if let Some(mu) = seen.insert(fingerprint(&state), i) {
+            let lambda = i - mu;
+            return (Cycle { mu, lambda }, state);
+        }
+        state = step(state);
+    }
+    unreachable!()
+}
+
+/// State at index `n`, only stepping through the cycle rather than all `n` states.
+pub fn nth_state<S: Clone + Eq>(initial: S, mut step: impl FnMut(S) -> S, n: usize) -> S {
+    let cycle = brent(initial.clone(), &mut step);
+    let mut state = initial;
+    for _ in 0..cycle.reduce(n) {
+        state = step(state);
+    }
+    state
+}
+
+/// First index at which every schedule has a hit, if there's any.
+pub fn first_common_hit(schedules: &[Schedule]) -> Option<usize> {
+    // Before the last cycle starts, the hits can simply be compared one by one
+    let tail = schedules.iter().map(|s| s.cycle.mu).max().unwrap_or(0);
+    if let Some(n) = (0..tail).find(|&n| schedules.iter().all(|s| s.hits_at(n))) {
+        return Some(n);
+    }
+
+    // Then each combination of hits within the cycles is a system of congruences
+    schedules
+        .iter()
+        .map(|s| {
+            s.hits
+                .iter()
+                .filter(|&&h| h >= s.cycle.mu)
+                .map(|&h| (h as i128, s.cycle.lambda as i128))
+        })
+        .multi_cartesian_product()
+        .filter_map(|congruences| num::crt(congruences).ok())
+        .map(|(n, period)| {
+            let tail = tail as i128;
+            if n < tail {
+                n + (tail - n + period - 1) / period * period
+            } else {
+                n
+            }
+        })
+        .min()
+        .and_then(|n| usize::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0, 1, 2, 3, 4, 5, 3, 4, 5, ...
+    fn step(n: u32) -> u32 {
+        if n == 5 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn detection() {
+        let expected = Cycle { mu: 3, lambda: 3 };
+        assert_eq!(brent(0, step), expected);
+        assert_eq!(fingerprint(0, step, |&n| n), (expected, 3));
+        assert_eq!(brent(4, step), Cycle { mu: 0, lambda: 3 });
+        assert_eq!(brent(7, |_| 7), Cycle { mu: 0, lambda: 1 });
+
+        assert_eq!(expected.reduce(2), 2);
+        assert_eq!(expected.reduce(9), 3);
+        assert_eq!(nth_state(0, step, 1_000_000_000), 4);
+        assert_eq!(nth_state(0, step, 1), 1);
+    }
+
+    #[test]
+    fn common_hits() {
+        let schedule = |mu, lambda, hits: &[usize]| Schedule {
+            cycle: Cycle { mu, lambda },
+            hits: hits.to_vec(),
+        };
+        // Hits at 2, 5, 8, ... and at 0, 4, 8, ...
+        let a = schedule(0, 3, &[2]);
+        let b = schedule(0, 4, &[0]);
+        assert!(b.hits_at(12) && !b.hits_at(13));
+        assert_eq!(first_common_hit(&[a.clone(), b]), Some(8));
+
+        // 1, 5, 9, ... with the cycle starting at 1
+        let b = schedule(1, 4, &[1]);
+        assert_eq!(first_common_hit(&[a, b]), Some(5));
+
+        // In the tail only
+        let c = schedule(3, 2, &[1, 3]);
+        let d = schedule(2, 2, &[1, 2]);
+        assert_eq!(first_common_hit(&[c.clone(), d]), Some(1));
+
+        let e = schedule(0, 2, &[0]);
+        let f = schedule(0, 2, &[1]);
+        assert_eq!(first_common_hit(&[e, f]), None);
+        assert_eq!(first_common_hit(&[c]), Some(1));
+    }
+}
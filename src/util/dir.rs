@@ -0,0 +1,133 @@
+use super::geom::Vec2;
+
+/// Orthogonal direction on a grid where `y` grows downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub const fn delta(self) -> Vec2<isize> {
+        match self {
+            Direction::Up => Vec2::new(0, -1),
+            Direction::Down => Vec2::new(0, 1),
+            Direction::Left => Vec2::new(-1, 0),
+            Direction::Right => Vec2::new(1, 0),
+        }
+    }
+
+    /// Turns 90 degrees counter-clockwise.
+    pub const fn left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+            Self::Right => Self::Up,
+        }
+    }
+
+    /// Turns 90 degrees clockwise.
+    pub const fn right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+            Self::Right => Self::Down,
+        }
+    }
+
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    pub const fn is_vertical(self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+
+    /// Bounces off a `\` mirror.
+    pub const fn reflect_l(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Down => Self::Right,
+            Self::Left => Self::Up,
+            Self::Right => Self::Down,
+        }
+    }
+
+    /// Bounces off a `/` mirror.
+    pub const fn reflect_r(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Down => Self::Left,
+            Self::Left => Self::Down,
+            Self::Right => Self::Up,
+        }
+    }
+
+    /// Moves `pos` by `len` steps in this direction.
+    pub fn step(self, pos: Vec2<isize>, len: isize) -> Vec2<isize> {
+        pos + self.delta() * len
+    }
+
+    /// Parses both relative (`U`/`D`/`L`/`R`) and compass (`N`/`S`/`W`/`E`) letters.
+    pub const fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'U' | 'N' => Self::Up,
+            'D' | 'S' => Self::Down,
+            'L' | 'W' => Self::Left,
+            'R' | 'E' => Self::Right,
+            _ => return None,
+        })
+    }
+
+    /// Parses the hex digit encoding from day 18 (`0` = right, then clockwise).
+    pub const fn from_hex_digit(c: char) -> Option<Self> {
+        Some(match c {
+            '0' => Self::Right,
+            '1' => Self::Down,
+            '2' => Self::Left,
+            '3' => Self::Up,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.left().right(), dir);
+            assert_eq!(dir.right().right(), dir.opposite());
+            assert_eq!(dir.reflect_l().reflect_l(), dir);
+            assert_eq!(dir.reflect_r().reflect_r(), dir);
+            assert_ne!(dir.is_vertical(), dir.left().is_vertical());
+        }
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(Direction::from_char('U'), Some(Direction::Up));
+        assert_eq!(Direction::from_char('W'), Some(Direction::Left));
+        assert_eq!(Direction::from_char('x'), None);
+        assert_eq!(Direction::from_hex_digit('1'), Some(Direction::Down));
+        assert_eq!(Direction::from_hex_digit('4'), None);
+    }
+}
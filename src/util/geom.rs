@@ -0,0 +1,312 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use num_traits::{NumCast, ToPrimitive, Zero};
+
+/// Point or displacement in the plane; on grids, `y` grows downwards.
+///
+/// The derived ordering is lexicographic, which only matters for sorted collections: use
+/// [`Vec2::all_le`] to compare component-wise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// Point or displacement in space, see [`Vec2`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// `|a - b|` without going through a signed type, so it also works for unsigned integers.
+fn abs_diff<T: PartialOrd + Sub<Output = T>>(a: T, b: T) -> T {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+fn min<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Vec2<U> {
+        Vec2::new(f(self.x), f(self.y))
+    }
+}
+
+impl<T> Vec3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Vec3<U> {
+        Vec3::new(f(self.x), f(self.y), f(self.z))
+    }
+
+    /// Projection onto the `xy` plane.
+    pub fn xy(self) -> Vec2<T> {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl<T: Copy + ToPrimitive> Vec2<T> {
+    /// Converts to another numeric type, or `None` if a component doesn't fit.
+    pub fn cast<U: NumCast>(self) -> Option<Vec2<U>> {
+        Some(Vec2::new(U::from(self.x)?, U::from(self.y)?))
+    }
+}
+
+impl<T: Copy + ToPrimitive> Vec3<T> {
+    /// Converts to another numeric type, or `None` if a component doesn't fit.
+    pub fn cast<U: NumCast>(self) -> Option<Vec3<U>> {
+        Some(Vec3::new(
+            U::from(self.x)?,
+            U::from(self.y)?,
+            U::from(self.z)?,
+        ))
+    }
+}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Vec2<T> {
+    pub fn dot(self, rhs: Self) -> T {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    /// `z` component of the 3D cross product: positive if `rhs` is counter-clockwise from `self`
+    /// with `y` going up, zero if they are parallel.
+    pub fn cross(self, rhs: Self) -> T {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    pub fn manhattan(self, rhs: Self) -> T {
+        abs_diff(self.x, rhs.x) + abs_diff(self.y, rhs.y)
+    }
+
+    pub fn chebyshev(self, rhs: Self) -> T {
+        max(abs_diff(self.x, rhs.x), abs_diff(self.y, rhs.y))
+    }
+
+    /// Every component is less than or equal to the one in `rhs`.
+    pub fn all_le(self, rhs: Self) -> bool {
+        self.x <= rhs.x && self.y <= rhs.y
+    }
+
+    pub fn min_each(self, rhs: Self) -> Self {
+        Self::new(min(self.x, rhs.x), min(self.y, rhs.y))
+    }
+
+    pub fn max_each(self, rhs: Self) -> Self {
+        Self::new(max(self.x, rhs.x), max(self.y, rhs.y))
+    }
+}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Vec3<T> {
+    pub fn dot(self, rhs: Self) -> T {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(self, rhs: Self) -> Self {
+        Self::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+
+    pub fn manhattan(self, rhs: Self) -> T {
+        abs_diff(self.x, rhs.x) + abs_diff(self.y, rhs.y) + abs_diff(self.z, rhs.z)
+    }
+
+    pub fn chebyshev(self, rhs: Self) -> T {
+        max(
+            max(abs_diff(self.x, rhs.x), abs_diff(self.y, rhs.y)),
+            abs_diff(self.z, rhs.z),
+        )
+    }
+
+    /// Every component is less than or equal to the one in `rhs`.
+    pub fn all_le(self, rhs: Self) -> bool {
+        self.x <= rhs.x && self.y <= rhs.y && self.z <= rhs.z
+    }
+
+    pub fn min_each(self, rhs: Self) -> Self {
+        Self::new(min(self.x, rhs.x), min(self.y, rhs.y), min(self.z, rhs.z))
+    }
+
+    pub fn max_each(self, rhs: Self) -> Self {
+        Self::new(max(self.x, rhs.x), max(self.y, rhs.y), max(self.z, rhs.z))
+    }
+}
+
+/// Component-wise operators, plus scaling by a number.
+macro_rules! ops {
+    ($name:ident { $($c:ident),* }) => {
+        impl<T: Add<Output = T>> Add for $name<T> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self { $($c: self.$c + rhs.$c),* }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $name<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($c: self.$c - rhs.$c),* }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $name<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($c: -self.$c),* }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $name<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self {
+                Self { $($c: self.$c * rhs),* }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $name<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$c += rhs.$c;)*
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $name<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$c -= rhs.$c;)*
+            }
+        }
+
+        impl<T: Zero> Zero for $name<T> {
+            fn zero() -> Self {
+                Self { $($c: T::zero()),* }
+            }
+
+            fn is_zero(&self) -> bool {
+                $(self.$c.is_zero())&&*
+            }
+        }
+    };
+}
+
+ops!(Vec2 { x, y });
+ops!(Vec3 { x, y, z });
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<Vec2<T>> for (T, T) {
+    fn from(v: Vec2<T>) -> Self {
+        (v.x, v.y)
+    }
+}
+
+impl<T> From<[T; 2]> for Vec2<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl<T> From<Vec3<T>> for (T, T, T) {
+    fn from(v: Vec3<T>) -> Self {
+        (v.x, v.y, v.z)
+    }
+}
+
+impl<T> From<[T; 3]> for Vec3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plane() {
+        let a = Vec2::new(3, -2);
+        let b = Vec2::from((-1, 4));
+        assert_eq!(a + b, Vec2::new(2, 2));
+        assert_eq!(a - b, Vec2::new(4, -6));
+        assert_eq!(-a * 2, Vec2::new(-6, 4));
+        assert_eq!(a.dot(b), -11);
+        assert_eq!(a.cross(b), 10);
+        assert_eq!(a.cross(a * 3), 0);
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!(a.min_each(b), Vec2::new(-1, -2));
+        assert!(!a.all_le(b) && a.min_each(b).all_le(b));
+
+        // Distances work without a sign too
+        let (p, q) = (Vec2::new(5usize, 1), Vec2::new(2usize, 7));
+        assert_eq!(p.manhattan(q), 9);
+        assert_eq!(q.manhattan(p), 9);
+        assert_eq!(p.cast::<isize>(), Some(Vec2::new(5, 1)));
+        assert_eq!(a.cast::<usize>(), None);
+        assert_eq!(<(i32, i32)>::from(a), (3, -2));
+
+        let mut c = Vec2::zero();
+        c += a;
+        c -= b;
+        assert_eq!(c, a - b);
+    }
+
+    #[test]
+    fn space() {
+        let x = Vec3::new(1, 0, 0);
+        let y = Vec3::from([0, 1, 0]);
+        assert_eq!(x.cross(y), Vec3::new(0, 0, 1));
+        assert_eq!(y.cross(x), Vec3::new(0, 0, -1));
+        assert_eq!(x.dot(y), 0);
+
+        let a = Vec3::new(1.5, -2.0, 4.0);
+        let b = Vec3::new(-0.5, 1.0, 2.0);
+        assert_eq!(a.manhattan(b), 7.0);
+        assert_eq!(a.chebyshev(b), 3.0);
+        assert_eq!(a.xy(), Vec2::new(1.5, -2.0));
+        assert_eq!(a.max_each(b), Vec3::new(1.5, 1.0, 4.0));
+        assert_eq!(
+            Vec3::new(2, -3, 7).cast::<f64>(),
+            Some(Vec3::new(2.0, -3.0, 7.0))
+        );
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::VecDeque;
+
+use fxhash::FxHashMap;
+
+use super::bit_set::DynBitSet;
+
+/// Maps names to dense `u32` ids, assigned in order of first appearance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interner {
+    ids: FxHashMap<Box<str>, u32>,
+    names: Vec<Box<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `name`, assigning the next free one if it's new.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        self.ids.insert(name.into(), id);
+        self.names.push(name.into());
+        id
+    }
+
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// All `(id, name)` pairs, by increasing id.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> + '_ {
+        self.names.iter().enumerate().map(|(i, n)| (i as u32, &**n))
+    }
+}
+
+/// Directed graph over dense node ids, the common interface of [`AdjList`] and [`Csr`].
+pub trait Graph {
+    fn node_count(&self) -> usize;
+
+    fn neighbors(&self, node: u32) -> &[u32];
+}
+
+/// Growable graph storing one list of outgoing edges per node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdjList {
+    edges: Vec<Vec<u32>>,
+}
+
+/// Compressed sparse row graph: every adjacency list packed in a single array.
+///
+/// Immutable, but cheaper to traverse than an [`AdjList`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Csr {
+    offsets: Vec<u32>,
+    targets: Vec<u32>,
+}
+
+impl AdjList {
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            edges: vec![Vec::new(); nodes],
+        }
+    }
+
+    /// Adds the edge `from -> to`, growing the graph if either node is new.
+    pub fn add_edge(&mut self, from: u32, to: u32) {
+        let needed = from.max(to) as usize + 1;
+        if self.edges.len() < needed {
+            self.edges.resize(needed, Vec::new());
+        }
+        self.edges[from as usize].push(to);
+    }
+
+    /// Adds both `a -> b` and `b -> a`, unless they are already connected.
+    pub fn add_undirected(&mut self, a: u32, b: u32) {
+        if !self.has_edge(a, b) {
+            self.add_edge(a, b);
+            self.add_edge(b, a);
+        }
+    }
+
+    pub fn has_edge(&self, from: u32, to: u32) -> bool {
+        self.edges
+            .get(from as usize)
+            .is_some_and(|e| e.contains(&to))
+    }
+
+    /// Removes the edge `from -> to`, returning whether it existed.
+    /// The order of `from`'s remaining neighbours isn't preserved.
+    pub fn remove_edge(&mut self, from: u32, to: u32) -> bool {
+        let Some(edges) = self.edges.get_mut(from as usize) else {
+            return false;
+        };
+        match edges.iter().position(|&n| n == to) {
+            Some(i) => {
+                edges.swap_remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Same graph with every edge pointing the other way.
+    pub fn reversed(&self) -> Self {
+        let mut res = Self::new(self.edges.len());
+        for (from, edges) in self.edges.iter().enumerate() {
+            for &to in edges {
+                res.edges[to as usize].push(from as u32);
+            }
+        }
+        res
+    }
+}
+
+impl Graph for AdjList {
+    fn node_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn neighbors(&self, node: u32) -> &[u32] {
+        &self.edges[node as usize]
+    }
+}
+
+impl From<&AdjList> for Csr {
+    fn from(list: &AdjList) -> Self {
+        let mut offsets = Vec::with_capacity(list.edges.len() + 1);
+        let mut targets = Vec::new();
+        offsets.push(0);
+        for edges in &list.edges {
+            targets.extend_from_slice(edges);
+            offsets.push(targets.len() as u32);
+        }
+        Self { offsets, targets }
+    }
+}
+
+impl Graph for Csr {
+    fn node_count(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    fn neighbors(&self, node: u32) -> &[u32] {
+        let node = node as usize;
+        &self.targets[self.offsets[node] as usize..self.offsets[node + 1] as usize]
+    }
+}
+
+/// Breadth-first traversal, yielding nodes with their distance from the start.
+pub struct Bfs<'a, G> {
+    graph: &'a G,
+    visited: DynBitSet,
+    queue: VecDeque<(u32, usize)>,
+}
+
+/// Depth-first traversal, yielding nodes in pre-order.
+pub struct Dfs<'a, G> {
+    graph: &'a G,
+    visited: DynBitSet,
+    stack: Vec<u32>,
+}
+
+pub fn bfs<G: Graph>(graph: &G, start: u32) -> Bfs<'_, G> {
+    let mut visited = DynBitSet::with_capacity(graph.node_count());
+    visited.insert(start as usize);
+    Bfs {
+        graph,
+        visited,
+        queue: VecDeque::from([(start, 0)]),
+    }
+}
+
+pub fn dfs<G: Graph>(graph: &G, start: u32) -> Dfs<'_, G> {
+    Dfs {
+        graph,
+        visited: DynBitSet::with_capacity(graph.node_count()),
+        stack: vec![start],
+    }
+}
+
+impl<G: Graph> Iterator for Bfs<'_, G> {
+    type Item = (u32, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, dist) = self.queue.pop_front()?;
+        for &neigh in self.graph.neighbors(node) {
+            if self.visited.insert(neigh as usize) {
+                self.queue.push_back((neigh, dist + 1));
+            }
+        }
+        Some((node, dist))
+    }
+}
+
+impl<G: Graph> Iterator for Dfs<'_, G> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+            if !self.visited.insert(node as usize) {
+                continue;
+            }
+            // Reversed so that neighbours are visited in order
+            self.stack.extend(
+                self.graph
+                    .neighbors(node)
+                    .iter()
+                    .rev()
+                    .filter(|&&n| !self.visited.contains(n as usize)),
+            );
+            return Some(node);
+        }
+    }
+}
+
+/// Fewest-edges path from `start` to `end`, both included.
+pub fn bfs_path<G: Graph>(graph: &G, start: u32, end: u32) -> Option<Vec<u32>> {
+    let mut parents = vec![u32::MAX; graph.node_count()];
+    let mut queue = VecDeque::from([start]);
+    parents[start as usize] = start;
+    while let Some(node) = queue.pop_front() {
+        if node == end {
+            let mut path = vec![end];
+            let mut cur = end;
+            while cur != start {
+                cur = parents[cur as usize];
+                path.push(cur);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &neigh in graph.neighbors(node) {
+            if parents[neigh as usize] == u32::MAX {
+                parents[neigh as usize] = node;
+                queue.push_back(neigh);
+            }
+        }
+    }
+    None
+}
+
+/// Connected components of an undirected graph, i.e. one where every edge goes both ways.
+pub fn components<G: Graph>(graph: &G) -> Vec<Vec<u32>> {
+    let mut seen = DynBitSet::with_capacity(graph.node_count());
+    let mut res = vec![];
+    for node in 0..graph.node_count() as u32 {
+        if seen.contains(node as usize) {
+            continue;
+        }
+        let component = dfs(graph, node).collect::<Vec<_>>();
+        for &n in &component {
+            seen.insert(n as usize);
+        }
+        res.push(component);
+    }
+    res
+}
+
+/// Orders nodes so that every edge points forward, or returns `None` if there is a cycle.
+pub fn topo_sort<G: Graph>(graph: &G) -> Option<Vec<u32>> {
+    let mut in_degree = vec![0usize; graph.node_count()];
+    for node in 0..graph.node_count() as u32 {
+        for &n in graph.neighbors(node) {
+            in_degree[n as usize] += 1;
+        }
+    }
+    let mut ready = (0..graph.node_count() as u32)
+        .filter(|&n| in_degree[n as usize] == 0)
+        .collect::<Vec<_>>();
+    let mut res = Vec::with_capacity(graph.node_count());
+    while let Some(node) = ready.pop() {
+        res.push(node);
+        for &n in graph.neighbors(node) {
+            in_degree[n as usize] -= 1;
+            if in_degree[n as usize] == 0 {
+                ready.push(n);
+            }
+        }
+    }
+    (res.len() == graph.node_count()).then_some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 -> 1 -> 2 -> 3, 0 -> 2, plus 4 on its own.
+    fn sample() -> AdjList {
+        let mut graph = AdjList::new(5);
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (0, 2)] {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+
+    #[test]
+    fn interner() {
+        let mut names = Interner::new();
+        assert_eq!(names.intern("AAA"), 0);
+        assert_eq!(names.intern("BBB"), 1);
+        assert_eq!(names.intern("AAA"), 0);
+        assert_eq!((names.get("BBB"), names.get("CCC")), (Some(1), None));
+        assert_eq!(names.name(1), "BBB");
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn traversals() {
+        let graph = sample();
+        let csr = Csr::from(&graph);
+        assert_eq!(csr.neighbors(0), graph.neighbors(0));
+        assert_eq!(csr.node_count(), 5);
+        assert_eq!(
+            bfs(&csr, 0).collect::<Vec<_>>(),
+            [(0, 0), (1, 1), (2, 1), (3, 2)]
+        );
+        assert_eq!(dfs(&graph, 0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(bfs_path(&graph, 0, 3), Some(vec![0, 2, 3]));
+        assert_eq!(bfs_path(&graph, 3, 0), None);
+        assert_eq!(bfs_path(&graph, 4, 4), Some(vec![4]));
+    }
+
+    #[test]
+    fn components_and_order() {
+        let mut graph = sample();
+        assert_eq!(topo_sort(&graph).map(|o| o.len()), Some(5));
+        let order = topo_sort(&graph).unwrap();
+        let pos = |n| order.iter().position(|&o| o == n).unwrap();
+        assert!(pos(0) < pos(1) && pos(1) < pos(2) && pos(2) < pos(3));
+
+        graph.add_edge(3, 0);
+        assert_eq!(topo_sort(&graph), None);
+
+        let mut undirected = AdjList::default();
+        for (a, b) in [(0, 1), (2, 3), (3, 4), (4, 3)] {
+            undirected.add_undirected(a, b);
+        }
+        assert_eq!(undirected.neighbors(3), [2, 4]);
+        assert_eq!(components(&undirected), [vec![0, 1], vec![2, 3, 4]]);
+        assert!(graph.remove_edge(3, 0) && !graph.remove_edge(3, 0));
+        assert_eq!(graph.reversed().neighbors(2), [0, 1]);
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    error, fmt,
+    ops::{Index, IndexMut},
+};
+
+use super::{dir::Direction, geom::Vec2};
+
+/// Dense, row-major 2D grid.
+///
+/// Positions are [`Vec2`]s, or `(x, y)` pairs converting into them. Checked accessors take
+/// signed coordinates so that neighbour arithmetic can step out of bounds without wrapping
+/// around `usize`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+/// Row of a character map whose width differs from the first row's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaggedRow {
+    /// 1-based, like line numbers.
+    pub row: usize,
+    pub width: usize,
+    pub expected: usize,
+}
+
+const DELTAS_8: [Vec2<isize>; 8] = [
+    Vec2::new(-1, -1),
+    Vec2::new(0, -1),
+    Vec2::new(1, -1),
+    Vec2::new(-1, 0),
+    Vec2::new(1, 0),
+    Vec2::new(-1, 1),
+    Vec2::new(0, 1),
+    Vec2::new(1, 1),
+];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "grid size mismatch");
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Builds a grid from a character map, one row per line. Puzzle inputs should rather go
+    /// through [`Source::grid`](crate::error::Source::grid), which locates bad cells too.
+    pub fn parse(input: &str, mut tile: impl FnMut(char) -> T) -> Result<Self, RaggedRow> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for line in input.lines() {
+            let start = cells.len();
+            cells.extend(line.chars().map(&mut tile));
+            height += 1;
+            let row_width = cells.len() - start;
+            let expected = *width.get_or_insert(row_width);
+            if row_width != expected {
+                return Err(RaggedRow {
+                    row: height,
+                    width: row_width,
+                    expected,
+                });
+            }
+        }
+        Ok(Self {
+            width: width.unwrap_or_default(),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: impl Into<Vec2<isize>>) -> bool {
+        let Vec2 { x, y } = pos.into();
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    pub fn get(&self, pos: impl Into<Vec2<isize>>) -> Option<&T> {
+        let pos = pos.into();
+        self.contains(pos)
+            .then(|| &self.cells[pos.y as usize * self.width + pos.x as usize])
+    }
+
+    pub fn get_mut(&mut self, pos: impl Into<Vec2<isize>>) -> Option<&mut T> {
+        let pos = pos.into();
+        if !self.contains(pos) {
+            return None;
+        }
+        Some(&mut self.cells[pos.y as usize * self.width + pos.x as usize])
+    }
+
+    /// Toroidal access: coordinates outside the grid wrap around to the opposite edge.
+    pub fn get_wrapping(&self, pos: impl Into<Vec2<isize>>) -> &T {
+        let Vec2 { x, y } = pos.into();
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    /// In-bounds orthogonal neighbours, in up/down/left/right order.
+    pub fn neighbors(&self, pos: impl Into<Vec2<isize>>) -> impl Iterator<Item = Vec2<isize>> + '_ {
+        let pos = pos.into();
+        Self::neighbors_wrapping(pos).filter(|&p| self.contains(p))
+    }
+
+    /// In-bounds orthogonal and diagonal neighbours, in reading order.
+    pub fn neighbors_8(
+        &self,
+        pos: impl Into<Vec2<isize>>,
+    ) -> impl Iterator<Item = Vec2<isize>> + '_ {
+        let pos = pos.into();
+        DELTAS_8
+            .into_iter()
+            .map(move |d| pos + d)
+            .filter(|&p| self.contains(p))
+    }
+
+    /// Orthogonal neighbours without bounds checks, to be used with [`Grid::get_wrapping`].
+    pub fn neighbors_wrapping(pos: impl Into<Vec2<isize>>) -> impl Iterator<Item = Vec2<isize>> {
+        let pos = pos.into();
+        Direction::ALL.into_iter().map(move |d| d.step(pos, 1))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + '_ {
+        // An empty grid has no cells to chunk anyway
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.cells[x..].iter().step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// All cells with their positions, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2<usize>, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (Vec2::new(i % self.width, i / self.width), t))
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn position(&self, pred: impl FnMut(&T) -> bool) -> Option<Vec2<usize>> {
+        self.cells
+            .iter()
+            .position(pred)
+            .map(|i| Vec2::new(i % self.width, i / self.width))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Self::new(self.height, self.width, cells)
+    }
+
+    /// Rotates the grid 90 degrees clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|x| self.column(x).rev().cloned())
+            .collect();
+        Self::new(self.height, self.width, cells)
+    }
+
+    /// Rotates the grid 90 degrees counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Self::new(self.height, self.width, cells)
+    }
+}
+
+impl<T, P: Into<Vec2<usize>>> Index<P> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: P) -> &Self::Output {
+        let Vec2 { x, y } = pos.into();
+        debug_assert!(x < self.width, "x = {x} out of bounds");
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T, P: Into<Vec2<usize>>> IndexMut<P> for Grid<T> {
+    fn index_mut(&mut self, pos: P) -> &mut Self::Output {
+        let Vec2 { x, y } = pos.into();
+        debug_assert!(x < self.width, "x = {x} out of bounds");
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl fmt::Display for RaggedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "row {} has width {}, expected {}",
+            self.row, self.width, self.expected
+        )
+    }
+}
+
+impl error::Error for RaggedRow {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse("abc\ndef", |c| c).unwrap()
+    }
+
+    #[test]
+    fn access() {
+        let grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get((1, 0)), Some(&'b'));
+        assert_eq!(grid.get((-1, 0)), None);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(*grid.get_wrapping((-1, 2)), 'c');
+        assert_eq!(grid.position(|&c| c == 'e'), Some(Vec2::new(1, 1)));
+        assert_eq!(grid[Vec2::new(0, 1)], 'd');
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = sample();
+        assert_eq!(
+            grid.neighbors((0, 0)).collect::<Vec<_>>(),
+            [Vec2::new(0, 1), Vec2::new(1, 0)]
+        );
+        assert_eq!(grid.neighbors_8((1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn views() {
+        let grid = sample();
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.columns().count(), 3);
+
+        let empty = Grid::parse("", |c| c).unwrap();
+        assert_eq!(empty.column(0).count(), 0);
+        assert_eq!(empty.rows().count(), 0);
+    }
+
+    #[test]
+    fn ragged() {
+        let err = Grid::parse("abc\nde\nfgh", |c| c).unwrap_err();
+        assert_eq!(
+            err,
+            RaggedRow {
+                row: 2,
+                width: 2,
+                expected: 3
+            }
+        );
+        assert_eq!(err.to_string(), "row 2 has width 2, expected 3");
+    }
+
+    #[test]
+    fn transforms() {
+        let grid = sample();
+        assert_eq!(Ok(grid.transpose()), Grid::parse("ad\nbe\ncf", |c| c));
+        assert_eq!(Ok(grid.rotate_cw()), Grid::parse("da\neb\nfc", |c| c));
+        assert_eq!(Ok(grid.rotate_ccw()), Grid::parse("cf\nbe\nad", |c| c));
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    iter::Sum,
+    ops::{Add, Range, Sub},
+};
+
+/// Set of values stored as sorted, disjoint and non-adjacent half-open ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+/// Map shifting each of a set of disjoint source ranges by its own offset, and leaving
+/// every other value unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseShift<T> {
+    /// Sorted by start.
+    pieces: Vec<(Range<T>, T)>,
+    covered: IntervalSet<T>,
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    /// The normalised ranges, in increasing order.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(i).is_some_and(|r| r.start <= value)
+    }
+
+    /// Adds `range`, merging it with every range it overlaps or touches.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let lo = self.ranges.partition_point(|r| r.end < range.start);
+        let hi = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if lo == hi {
+            range
+        } else {
+            self.ranges[lo].start.min(range.start)..self.ranges[hi - 1].end.max(range.end)
+        };
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        for r in &other.ranges {
+            res.insert(r.clone());
+        }
+        res
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let start = x.start.max(y.start);
+            let end = x.end.min(y.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            // Drop whichever ends first, it can't overlap anything further
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut others = other.ranges.iter().peekable();
+        for r in &self.ranges {
+            let mut start = r.start;
+            while others.next_if(|o| o.end <= start).is_some() {}
+            for o in others.clone() {
+                if o.start >= r.end {
+                    break;
+                }
+                if o.start > start {
+                    ranges.push(start..o.start);
+                }
+                start = start.max(o.end);
+            }
+            if start < r.end {
+                ranges.push(start..r.end);
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Sum of the lengths of every range.
+    pub fn len(&self) -> T
+    where
+        T: Sub<Output = T> + Sum,
+    {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+}
+
+impl<T: Copy + Ord> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut res = Self::new();
+        res.insert(range);
+        res
+    }
+}
+
+/// Normalises arbitrary ranges: empty ones are dropped, overlapping and adjacent ones coalesced.
+impl<T: Copy + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut ranges = iter
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|r| r.start);
+        let mut res: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for r in ranges {
+            match res.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => res.push(r),
+            }
+        }
+        Self { ranges: res }
+    }
+}
+
+impl<T: Copy + Ord + Add<Output = T>> PiecewiseShift<T> {
+    pub fn new() -> Self {
+        Self {
+            pieces: vec![],
+            covered: IntervalSet::new(),
+        }
+    }
+
+    /// Shifts values in `source` by `offset`; where pieces overlap, the first one added wins.
+    pub fn add(&mut self, source: Range<T>, offset: T) {
+        let new = IntervalSet::from(source).difference(&self.covered);
+        for r in new.ranges {
+            self.covered.insert(r.clone());
+            let i = self.pieces.partition_point(|(p, _)| p.start < r.start);
+            self.pieces.insert(i, (r, offset));
+        }
+    }
+
+    pub fn apply(&self, value: T) -> T {
+        let i = self.pieces.partition_point(|(p, _)| p.end <= value);
+        match self.pieces.get(i) {
+            Some((p, offset)) if p.start <= value => value + *offset,
+            _ => value,
+        }
+    }
+
+    /// Image of every value in `set`.
+    pub fn apply_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut res = set.difference(&self.covered);
+        for (piece, offset) in &self.pieces {
+            for r in set.intersection(&IntervalSet::from(piece.clone())).ranges {
+                res.insert(r.start + *offset..r.end + *offset);
+            }
+        }
+        res
+    }
+}
+
+impl<T: Copy + Ord + Add<Output = T>> Default for PiecewiseShift<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalisation() {
+        let set = IntervalSet::from_iter([5..8, 0..2, 1..3, 8..9, 4..4, 12..15]);
+        assert_eq!(set.ranges(), [0..3, 5..9, 12..15]);
+        assert_eq!(set.len(), 10);
+        assert_eq!(set.min(), Some(0));
+        assert!(set.contains(8) && !set.contains(9) && !set.contains(3));
+
+        let mut set = set;
+        set.insert(3..5);
+        assert_eq!(set.ranges(), [0..9, 12..15]);
+        set.insert(10..11);
+        assert_eq!(set.ranges(), [0..9, 10..11, 12..15]);
+        set.insert(-5..20);
+        assert_eq!(set, IntervalSet::from(-5..20));
+    }
+
+    #[test]
+    fn algebra() {
+        let a = IntervalSet::from_iter([0..5, 10..15, 20..25]);
+        let b = IntervalSet::from_iter([3..12, 14..21, 30..40]);
+        assert_eq!(a.union(&b).ranges(), [0..25, 30..40]);
+        assert_eq!(a.intersection(&b).ranges(), [3..5, 10..12, 14..15, 20..21]);
+        assert_eq!(a.difference(&b).ranges(), [0..3, 12..14, 21..25]);
+        assert_eq!(b.difference(&a).ranges(), [5..10, 15..20, 30..40]);
+        assert!(a.intersection(&IntervalSet::new()).is_empty());
+        assert_eq!(
+            a.difference(&IntervalSet::from(-10..100)),
+            IntervalSet::new()
+        );
+    }
+
+    #[test]
+    fn shift() {
+        // Day 5's seed-to-soil map
+        let mut map = PiecewiseShift::new();
+        map.add(98..100, -48);
+        map.add(50..98, 2);
+        map.add(40..60, 100);
+        assert_eq!(map.apply(79), 81);
+        assert_eq!(map.apply(99), 51);
+        assert_eq!(map.apply(45), 145);
+        assert_eq!(map.apply(55), 57);
+        assert_eq!(map.apply(100), 100);
+
+        let image = map.apply_set(&IntervalSet::from_iter([45..52, 97..101]));
+        assert_eq!(image.ranges(), [50..54, 99..101, 145..150]);
+    }
+}
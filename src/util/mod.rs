@@ -1 +1,13 @@
 pub mod bit_set;
+pub mod cycle;
+pub mod dir;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod interval;
+pub mod num;
+pub mod parse;
+pub mod poly;
+pub mod polygon;
+pub mod search;
+pub mod tokens;
@@ -0,0 +1,185 @@
+use std::{error, fmt};
+
+use num_traits::{CheckedMul, Euclid, Num, Signed};
+
+/// Integers the helpers below work with: primitives as well as `BigInt`.
+pub trait Integer: Num + Clone + Ord + CheckedMul + Euclid {}
+
+impl<T: Num + Clone + Ord + CheckedMul + Euclid> Integer for T {}
+
+/// Why a system of congruences has no solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    /// Two congruences contradict each other, e.g. `x = 0 mod 2` and `x = 1 mod 4`.
+    Inconsistent,
+    /// An intermediate value, like the combined modulus, doesn't fit in the integer type.
+    Overflow,
+    /// A modulus is zero or negative.
+    InvalidModulus,
+}
+
+/// Greatest common divisor, always non-negative; `gcd(0, 0) = 0`.
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> T {
+    while !b.is_zero() {
+        let r = a.rem_euclid(&b);
+        a = b;
+        b = r;
+    }
+    if a < T::zero() {
+        T::zero() - a
+    } else {
+        a
+    }
+}
+
+/// Least common multiple, or `None` if it overflows; `lcm(0, x) = 0`.
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+    // Dividing first keeps the intermediate value as small as the result
+    let res = (a.clone() / gcd(a, b.clone())).checked_mul(&b)?;
+    Some(if res < T::zero() {
+        T::zero() - res
+    } else {
+        res
+    })
+}
+
+/// Least common multiple, panicking on overflow.
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("lcm overflowed")
+}
+
+/// Least common multiple of all `nums`, or `None` if it overflows. Empty input gives 1.
+pub fn lcm_all<T: Integer>(nums: impl IntoIterator<Item = T>) -> Option<T> {
+    nums.into_iter().try_fold(T::one(), checked_lcm)
+}
+
+/// Extended Euclid: `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+pub fn egcd<T: Integer + Signed>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r.clone() / r.clone();
+        let next_r = old_r - q.clone() * r.clone();
+        (old_r, r) = (r, next_r);
+        let next_x = old_x - q.clone() * x.clone();
+        (old_x, x) = (x, next_x);
+        let next_y = old_y - q * y.clone();
+        (old_y, y) = (y, next_y);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Inverse of `a` modulo `m`, in `0..m`, if they are coprime.
+pub fn mod_inv<T: Integer + Signed>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = egcd(a.rem_euclid(&m), m.clone());
+    g.is_one().then(|| x.rem_euclid(&m))
+}
+
+/// Solves the system `x = residue mod modulus` for every pair, moduli need not be coprime.
+///
+/// Returns the smallest non-negative solution and the combined modulus, i.e. the lcm of
+/// all moduli: every solution is congruent to the first one modulo the second.
+pub fn crt<T: Integer + Signed>(
+    congruences: impl IntoIterator<Item = (T, T)>,
+) -> Result<(T, T), CrtError> {
+    let (mut x, mut m) = (T::zero(), T::one());
+    for (residue, modulus) in congruences {
+        if !modulus.is_positive() {
+            return Err(CrtError::InvalidModulus);
+        }
+        // x + m * k = residue mod modulus, with m * p + modulus * _ = g
+        let (g, p, _) = egcd(m.clone(), modulus.clone());
+        let diff = residue.rem_euclid(&modulus) - x.clone();
+        if !diff.rem_euclid(&g).is_zero() {
+            return Err(CrtError::Inconsistent);
+        }
+        let step = modulus / g.clone();
+        let k = (diff / g).rem_euclid(&step);
+        let k = k
+            .checked_mul(&p.rem_euclid(&step))
+            .ok_or(CrtError::Overflow)?
+            .rem_euclid(&step);
+        let next_m = m.checked_mul(&step).ok_or(CrtError::Overflow)?;
+        x = (x + m * k).rem_euclid(&next_m);
+        m = next_m;
+    }
+    Ok((x, m))
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrtError::Inconsistent => write!(f, "inconsistent congruences"),
+            CrtError::Overflow => write!(f, "integer overflow"),
+            CrtError::InvalidModulus => write!(f, "moduli must be positive"),
+        }
+    }
+}
+
+impl error::Error for CrtError {}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use super::*;
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(gcd(12i64, 18), 6);
+        assert_eq!(gcd(-12i64, 18), 6);
+        assert_eq!(gcd(0i64, 0), 0);
+        assert_eq!(lcm(4usize, 6), 12);
+        assert_eq!(lcm_all([1usize, 2, 3, 12]), Some(12));
+        assert_eq!(lcm_all([5usize, 4]), Some(20));
+        assert_eq!(lcm_all(Vec::<u32>::new()), Some(1));
+        // The product overflows, the lcm doesn't
+        assert_eq!(checked_lcm(1u64 << 40, 1 << 41), Some(1 << 41));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(
+            lcm(BigInt::from(u64::MAX), BigInt::from(2)),
+            BigInt::from(u64::MAX) * 2
+        );
+    }
+
+    #[test]
+    fn inverses() {
+        let (g, x, y) = egcd(240i64, 46);
+        assert_eq!((g, 240 * x + 46 * y), (2, 2));
+        assert_eq!(egcd(-4i128, 6).0, 2);
+        assert_eq!(mod_inv(3i64, 11), Some(4));
+        assert_eq!(mod_inv(-3i64, 11), Some(7));
+        assert_eq!(mod_inv(4i64, 8), None);
+        assert_eq!(
+            mod_inv(BigInt::from(3), BigInt::from(11)),
+            Some(BigInt::from(4))
+        );
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt([(2i64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt([(1i64, 4), (3, 6)]), Ok((9, 12)));
+        assert_eq!(crt([(0i64, 2), (1, 4)]), Err(CrtError::Inconsistent));
+        assert_eq!(crt([(1i64, 0)]), Err(CrtError::InvalidModulus));
+        assert_eq!(crt(Vec::<(i64, i64)>::new()), Ok((0, 1)));
+        assert_eq!(
+            crt([(0i64, i64::MAX), (1, i64::MAX - 1)]),
+            Err(CrtError::Overflow)
+        );
+        let big = |n: i64| BigInt::from(n);
+        assert_eq!(
+            crt([(big(0), big(i64::MAX)), (big(1), big(i64::MAX - 1))]).map(|(_, m)| m),
+            Ok(big(i64::MAX) * big(i64::MAX - 1))
+        );
+    }
+}
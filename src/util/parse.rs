@@ -0,0 +1,253 @@
+use std::{marker::PhantomData, ops::Range};
+
+/// Integer type that can be scanned out of ASCII digits.
+pub trait Int: Copy + 'static {
+    const SIGNED: bool;
+    const ZERO: Self;
+
+    /// `self * 10 ± digit`, or `None` on overflow.
+    fn push_digit(self, digit: u8, negative: bool) -> Option<Self>;
+}
+
+macro_rules! int {
+    ($signed:literal => $($t:ty),*) => {$(
+        impl Int for $t {
+            const SIGNED: bool = $signed;
+            const ZERO: Self = 0;
+
+            fn push_digit(self, digit: u8, negative: bool) -> Option<Self> {
+                let res = self.checked_mul(10)?;
+                if negative {
+                    res.checked_sub(digit as Self)
+                } else {
+                    res.checked_add(digit as Self)
+                }
+            }
+        }
+    )*};
+}
+
+int!(false => u8, u16, u32, u64, u128, usize);
+int!(true => i8, i16, i32, i64, i128, isize);
+
+/// Byte-level cursor picking integers out of arbitrary text.
+///
+/// Anything that isn't a digit separates numbers. For signed types, a `-` right before a digit
+/// is a sign, so `1-2` gives `1, -2` as `i32` but `1, 2` as `u32`.
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+/// Byte range of an integer along with its value, or `None` if it overflows.
+pub type Scanned<T> = (Range<usize>, Option<T>);
+
+/// Iterator over the integers in a string, see [`ints`].
+#[derive(Debug, Clone)]
+pub struct Ints<'a, T> {
+    text: &'a str,
+    scanner: Scanner<'a>,
+    separators: &'a str,
+    /// Set after a bad separator, past which the numbers can't be trusted.
+    failed: bool,
+    _int: PhantomData<T>,
+}
+
+/// Why integers couldn't be scanned out of a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanError<'a> {
+    /// This number doesn't fit in the integer type.
+    Overflow(&'a str),
+    /// This text between numbers isn't made of the allowed separators.
+    Separator(&'a str),
+    /// The text ran out of numbers.
+    Missing,
+    /// First of the numbers left over.
+    Extra(&'a str),
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Offset of the next byte to be scanned.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Scans the next integer, returning its byte range along with the value, or `None` for the
+    /// value if it overflows `T`.
+    pub fn next_int<T: Int>(&mut self) -> Option<Scanned<T>> {
+        let bytes = self.bytes;
+        let digit_at = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+        let start = (self.pos..bytes.len())
+            .find(|&i| digit_at(i) || (T::SIGNED && bytes[i] == b'-' && digit_at(i + 1)))?;
+        let negative = bytes[start] == b'-';
+
+        let mut end = start + negative as usize;
+        let mut value = Some(T::ZERO);
+        while digit_at(end) {
+            value = value.and_then(|v| v.push_digit(bytes[end] - b'0', negative));
+            end += 1;
+        }
+        self.pos = end;
+        Some((start..end, value))
+    }
+}
+
+impl<'a, T: Int> Ints<'a, T> {
+    /// Scans the next integer like [`Scanner::next_int`], checking the text before it, or
+    /// before the end if there are no more integers.
+    fn next_int(&mut self) -> Option<Result<Scanned<T>, ScanError<'a>>> {
+        if self.failed {
+            return None;
+        }
+        let gap_start = self.scanner.pos();
+        let next = self.scanner.next_int();
+        let gap_end = next
+            .as_ref()
+            .map_or(self.text.len(), |(range, _)| range.start);
+        // Numbers start and end on ASCII bytes, so gaps are on character boundaries
+        let gap = &self.text[gap_start..gap_end];
+        if let Some(start) = gap.find(|c| !self.separators.contains(c)) {
+            self.failed = true;
+            let bad = &gap[start..];
+            let end = bad
+                .find(|c: char| self.separators.contains(c) || c.is_ascii_digit())
+                .unwrap_or(bad.len());
+            return Some(Err(ScanError::Separator(&bad[..end])));
+        }
+        next.map(Ok)
+    }
+}
+
+impl<'a, T: Int> Iterator for Ints<'a, T> {
+    /// The number, or why it couldn't be read.
+    type Item = Result<T, ScanError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.next_int()?
+                .and_then(|(range, value)| value.ok_or(ScanError::Overflow(&self.text[range]))),
+        )
+    }
+}
+
+/// Every integer in `text`, in order, only separated by characters of `separators`.
+///
+/// Text before the first number and after the last one counts as separators too. For signed
+/// types, the `-` of a negative number is part of it rather than a separator.
+pub fn ints<'a, T: Int>(text: &'a str, separators: &'a str) -> Ints<'a, T> {
+    Ints {
+        text,
+        scanner: Scanner::new(text.as_bytes()),
+        separators,
+        failed: false,
+        _int: PhantomData,
+    }
+}
+
+/// Exactly `N` integers out of `text`, see [`ints`].
+pub fn ints_n<'a, T: Int, const N: usize>(
+    text: &'a str,
+    separators: &'a str,
+) -> Result<[T; N], ScanError<'a>> {
+    let mut res = [T::ZERO; N];
+    let mut ints = ints(text, separators);
+    for slot in &mut res {
+        *slot = ints.next().ok_or(ScanError::Missing)??;
+    }
+    match ints.next_int() {
+        Some(Ok((range, _))) => Err(ScanError::Extra(&text[range])),
+        Some(Err(err)) => Err(err),
+        None => Ok(res),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scanning() {
+        let text = "12: -3, +7 | 255 256";
+        assert_eq!(
+            ints::<i64>(text, ":,+| ").collect::<Result<Vec<_>, _>>(),
+            Ok(vec![12, -3, 7, 255, 256])
+        );
+        assert_eq!(
+            ints::<u32>(text, "-:,+| ").collect::<Result<Vec<_>, _>>(),
+            Ok(vec![12, 3, 7, 255, 256])
+        );
+        assert_eq!(
+            ints::<u8>(text, "-:,+| ").collect::<Vec<_>>(),
+            [
+                Ok(12),
+                Ok(3),
+                Ok(7),
+                Ok(255),
+                Err(ScanError::Overflow("256"))
+            ]
+        );
+        assert_eq!(
+            ints::<i8>("-128 -129 --5", "- ").collect::<Vec<_>>(),
+            [Ok(-128), Err(ScanError::Overflow("-129")), Ok(-5)]
+        );
+        assert_eq!(ints::<u64>("  ", " ").next(), None);
+
+        let mut scanner = Scanner::new(b"ab 42cd");
+        assert_eq!(scanner.next_int::<u16>(), Some((3..5, Some(42))));
+        assert_eq!(scanner.pos(), 5);
+        assert_eq!(scanner.next_int::<u16>(), None);
+    }
+
+    #[test]
+    fn separators() {
+        // Scanning stops at the first bad separator
+        assert_eq!(
+            ints::<u32>("1 2 x3 4", " ").collect::<Vec<_>>(),
+            [Ok(1), Ok(2), Err(ScanError::Separator("x"))]
+        );
+        assert_eq!(
+            ints::<u32>("1,2", " ").collect::<Vec<_>>(),
+            [Ok(1), Err(ScanError::Separator(","))]
+        );
+        assert_eq!(
+            ints::<u32>("1 2 éh ", " ").collect::<Vec<_>>(),
+            [Ok(1), Ok(2), Err(ScanError::Separator("éh"))]
+        );
+        assert_eq!(
+            ints::<i32>("-1 - 2", " ").collect::<Vec<_>>(),
+            [Ok(-1), Err(ScanError::Separator("-"))]
+        );
+        assert_eq!(
+            ints::<u32>("seeds: 1", " ").collect::<Vec<_>>(),
+            [Err(ScanError::Separator("seeds:"))]
+        );
+    }
+
+    #[test]
+    fn fixed_arity() {
+        assert_eq!(
+            ints_n::<isize, 6>("1,0,1~-1,2,1", ",~"),
+            Ok([1, 0, 1, -1, 2, 1])
+        );
+        assert_eq!(ints_n::<u32, 3>("1 2", " "), Err(ScanError::Missing));
+        assert_eq!(ints_n::<u32, 2>("1 2 33", " "), Err(ScanError::Extra("33")));
+        assert_eq!(
+            ints_n::<u32, 2>("1 2 ;", " "),
+            Err(ScanError::Separator(";"))
+        );
+        assert_eq!(
+            ints_n::<u32, 2>("1;2 3", " "),
+            Err(ScanError::Separator(";"))
+        );
+        assert_eq!(
+            ints_n::<u8, 2>("1 999", " "),
+            Err(ScanError::Overflow("999"))
+        );
+        assert_eq!(ints_n::<u8, 0>("", " "), Ok([]));
+    }
+}
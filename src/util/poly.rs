@@ -0,0 +1,174 @@
+use std::{
+    fmt,
+    ops::{Add, Mul},
+};
+
+use super::num;
+
+/// Exact fraction, always kept reduced with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    pub fn new(num: i128, den: i128) -> Self {
+        assert_ne!(den, 0, "zero denominator");
+        let g = num::gcd(num, den) * den.signum();
+        Self {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    /// The value, if it is a whole number.
+    pub fn to_integer(&self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+impl From<i128> for Ratio {
+    fn from(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+}
+
+impl Add for Ratio {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let den = num::lcm(self.den, rhs.den);
+        Self::new(self.num * (den / self.den) + rhs.num * (den / rhs.den), den)
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        // Cross-reduce first to keep the products small
+        let a = Self::new(self.num, rhs.den);
+        let b = Self::new(rhs.num, self.den);
+        Self::new(a.num * b.num, a.den * b.den)
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_integer() {
+            Some(n) => write!(f, "{n}"),
+            None => write!(f, "{}/{}", self.num, self.den),
+        }
+    }
+}
+
+/// Newton forward differences of samples taken at `x = 0, 1, 2, ...`: the first value of each
+/// successive difference row, i.e. `[y0, Δy0, Δ²y0, ...]`.
+pub fn forward_differences(samples: &[i128]) -> Vec<i128> {
+    let mut row = samples.to_vec();
+    let mut res = Vec::with_capacity(row.len());
+    // Each row overwrites the previous one in place
+    for len in (1..=row.len()).rev() {
+        res.push(row[0]);
+        for i in 0..len - 1 {
+            row[i] = row[i + 1] - row[i];
+        }
+    }
+    res
+}
+
+/// Degree of the polynomial the samples follow, if they are enough to tell: the `degree + 1`-th
+/// differences must exist and all be zero. A constant zero counts as degree 0.
+pub fn degree(samples: &[i128]) -> Option<usize> {
+    let mut row = samples.to_vec();
+    for degree in 0..samples.len().saturating_sub(1) {
+        for i in 0..row.len() - 1 {
+            row[i] = row[i + 1] - row[i];
+        }
+        row.pop();
+        if row.iter().all(|&d| d == 0) {
+            return Some(degree);
+        }
+    }
+    None
+}
+
+/// Value at `x` of the lowest-degree polynomial through samples taken at `x = 0, 1, 2, ...`.
+///
+/// Works for any `x`, before the samples as well as after them, from Newton's forward formula:
+/// `P(x) = Σ C(x, k) Δᵏy0`, where the generalized binomials are always integers.
+pub fn extrapolate(samples: &[i128], x: i128) -> i128 {
+    let mut binomial = 1;
+    let mut res = 0;
+    for (k, diff) in forward_differences(samples).into_iter().enumerate() {
+        if k > 0 {
+            binomial = binomial * (x - k as i128 + 1) / k as i128;
+        }
+        res += binomial * diff;
+    }
+    res
+}
+
+/// Value at `x` of the lowest-degree polynomial through `points`, whose `x`s must be distinct.
+pub fn lagrange(points: &[(i128, i128)], x: i128) -> Ratio {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(xi, yi))| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(Ratio::from(yi), |acc, (_, &(xj, _))| {
+                    acc * Ratio::new(x - xj, xi - xj)
+                })
+        })
+        .fold(Ratio::from(0), Add::add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differences() {
+        // n² + 1
+        let squares = [1, 2, 5, 10, 17];
+        assert_eq!(forward_differences(&squares), [1, 1, 2, 0, 0]);
+        assert_eq!(degree(&squares), Some(2));
+        assert_eq!(degree(&[3, 3]), Some(0));
+        assert_eq!(degree(&[0, 0, 0]), Some(0));
+        assert_eq!(degree(&[1, 2, 4, 8]), None);
+        assert_eq!(degree(&[7]), None);
+
+        assert_eq!(extrapolate(&squares, 5), 26);
+        assert_eq!(extrapolate(&squares, -3), 10);
+        assert_eq!(extrapolate(&squares, 2), 5);
+        assert_eq!(extrapolate(&[1, 2, 4, 8], 4), 15);
+        assert_eq!(extrapolate(&[], 4), 0);
+    }
+
+    #[test]
+    fn interpolation() {
+        // n² + 1 again, sampled out of order and unevenly
+        let points = [(3, 10), (-2, 5), (7, 50)];
+        assert_eq!(lagrange(&points, 4).to_integer(), Some(17));
+        assert_eq!(lagrange(&points, -10), Ratio::from(101));
+        // A line through (0, 0) and (2, 1)
+        let half = lagrange(&[(0, 0), (2, 1)], 1);
+        assert_eq!((half.numer(), half.denom()), (1, 2));
+        assert_eq!(half.to_integer(), None);
+        assert_eq!(half.to_string(), "1/2");
+        assert_eq!(Ratio::new(4, -6), Ratio::new(-2, 3));
+        assert_eq!(half + Ratio::new(1, 3), Ratio::new(5, 6));
+    }
+}
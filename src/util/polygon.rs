@@ -0,0 +1,155 @@
+use super::{dir::Direction, geom::Vec2, num};
+
+/// Simple polygon with integer vertices, implicitly closed from the last vertex back to the first.
+///
+/// Everything is computed exactly in `i128`, with the area kept doubled since a general
+/// lattice polygon can have a half-integer area.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Vec2<i128>>,
+}
+
+/// Where a point lies relative to a [`Polygon`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+impl Polygon {
+    /// Builds a polygon from its vertices in order; repeating the first one at the end is optional.
+    pub fn new(vertices: impl IntoIterator<Item = impl Into<Vec2<i128>>>) -> Self {
+        let mut vertices = vertices.into_iter().map(Into::into).collect::<Vec<_>>();
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    /// Builds the rectilinear polygon traced by walking from `start`, one straight line per step.
+    pub fn from_walk(
+        start: impl Into<Vec2<i128>>,
+        steps: impl IntoIterator<Item = (Direction, i128)>,
+    ) -> Self {
+        let start = start.into();
+        let mut cur = start;
+        Self::new(
+            std::iter::once(start).chain(steps.into_iter().map(|(dir, len)| {
+                cur += dir.delta().map(|d| d as i128) * len;
+                cur
+            })),
+        )
+    }
+
+    pub fn vertices(&self) -> &[Vec2<i128>] {
+        &self.vertices
+    }
+
+    /// Every edge is horizontal or vertical.
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges().all(|(a, b)| a.x == b.x || a.y == b.y)
+    }
+
+    /// Twice the shoelace area: positive if the vertices turn counter-clockwise with `y` going up,
+    /// i.e. clockwise on a grid where `y` grows downwards.
+    pub fn doubled_signed_area(&self) -> i128 {
+        self.edges().map(|(a, b)| a.cross(b)).sum()
+    }
+
+    /// Lattice points on the edges, vertices included.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| {
+                let d = b - a;
+                num::gcd(d.x.abs(), d.y.abs())
+            })
+            .sum()
+    }
+
+    /// Lattice points strictly inside, from Pick's theorem: `A = I + B / 2 - 1`.
+    pub fn interior_points(&self) -> i128 {
+        (self.doubled_signed_area().abs() - self.boundary_points() + 2) / 2
+    }
+
+    /// Lattice points inside or on the boundary.
+    pub fn enclosed_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn locate(&self, p: impl Into<Vec2<i128>>) -> Location {
+        let p = p.into();
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let cross = (b - a).cross(p - a);
+            let within = a.min_each(b).all_le(p) && p.all_le(a.max_each(b));
+            if cross == 0 && within {
+                return Location::Boundary;
+            }
+            // Casting a ray towards +x, count the edges crossing it
+            if (a.y > p.y) != (b.y > p.y) && (cross > 0) == (b.y > a.y) {
+                inside = !inside;
+            }
+        }
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Vec2<i128>, Vec2<i128>)> + '_ {
+        self.vertices
+            .iter()
+            .copied()
+            .zip(self.vertices.iter().copied().cycle().skip(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectilinear() {
+        // 3x2 rectangle walked clockwise on screen, then backwards
+        let rect = Polygon::from_walk(
+            (0, 0),
+            [
+                (Direction::Right, 3),
+                (Direction::Down, 2),
+                (Direction::Left, 3),
+                (Direction::Up, 2),
+            ],
+        );
+        assert_eq!(rect.vertices().len(), 4);
+        assert!(rect.is_rectilinear());
+        assert_eq!(rect.doubled_signed_area(), 12);
+        assert_eq!(rect.boundary_points(), 10);
+        assert_eq!(rect.interior_points(), 2);
+        assert_eq!(rect.enclosed_points(), 12);
+
+        let reversed = Polygon::new(rect.vertices().iter().rev().copied());
+        assert_eq!(reversed.doubled_signed_area(), -12);
+        assert_eq!(reversed.interior_points(), 2);
+    }
+
+    #[test]
+    fn general() {
+        let triangle = Polygon::new([(0, 0), (4, 0), (0, 3), (0, 0)]);
+        assert_eq!(triangle.vertices().len(), 3);
+        assert!(!triangle.is_rectilinear());
+        assert_eq!(triangle.doubled_signed_area(), 12);
+        // 4 + 1 + 3 on the edges, 3 points strictly inside
+        assert_eq!(triangle.boundary_points(), 8);
+        assert_eq!(triangle.interior_points(), 3);
+
+        assert_eq!(triangle.locate((1, 1)), Location::Inside);
+        assert_eq!(triangle.locate((2, 1)), Location::Inside);
+        assert_eq!(triangle.locate((3, 1)), Location::Outside);
+        assert_eq!(triangle.locate((4, 0)), Location::Boundary);
+        assert_eq!(triangle.locate((0, 2)), Location::Boundary);
+        assert_eq!(triangle.locate((-1, 0)), Location::Outside);
+        assert_eq!(triangle.locate((5, 0)), Location::Outside);
+    }
+}
@@ -0,0 +1,216 @@
+use std::{cmp::Reverse, collections::BinaryHeap, hash::Hash, ops::Add};
+
+use fxhash::FxHashMap;
+
+/// Cheapest way found to reach a goal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    /// Every state along the way, from the start to the goal, both included.
+    pub states: Vec<S>,
+}
+
+/// States discovered so far, each with its best known cost and the state it was reached from.
+struct Visited<S, C> {
+    ids: FxHashMap<S, usize>,
+    states: Vec<S>,
+    best: Vec<(C, usize)>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy + Ord> Visited<S, C> {
+    fn new(start: S, cost: C) -> Self {
+        let mut ids = FxHashMap::default();
+        ids.insert(start.clone(), 0);
+        Self {
+            ids,
+            states: vec![start],
+            best: vec![(cost, 0)],
+        }
+    }
+
+    /// Records that `state` can be reached from `parent` for `cost`, returning its id
+    /// if that's better than what was known.
+    fn relax(&mut self, state: S, cost: C, parent: usize) -> Option<usize> {
+        match self.ids.get(&state) {
+            Some(&id) if self.best[id].0 <= cost => None,
+            Some(&id) => {
+                self.best[id] = (cost, parent);
+                Some(id)
+            }
+            None => {
+                let id = self.states.len();
+                self.ids.insert(state.clone(), id);
+                self.states.push(state);
+                self.best.push((cost, parent));
+                Some(id)
+            }
+        }
+    }
+
+    fn path(&self, mut id: usize) -> Path<S, C> {
+        let cost = self.best[id].0;
+        let mut ids = vec![id];
+        while id != 0 {
+            id = self.best[id].1;
+            ids.push(id);
+        }
+        let states = ids
+            .into_iter()
+            .rev()
+            .map(|id| self.states[id].clone())
+            .collect();
+        Path { cost, states }
+    }
+}
+
+/// Cheapest path from `start` to any state satisfying `goal`, for non-negative costs.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    neighbors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, neighbors, |_| C::default(), goal)
+}
+
+/// Dijkstra guided by `heuristic`, a lower bound of the remaining cost to a goal.
+///
+/// The path is only guaranteed to be the cheapest if the heuristic never overestimates.
+pub fn astar<S, C, I>(
+    start: S,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((heuristic(&start), C::default(), 0)));
+    let mut visited = Visited::new(start, C::default());
+
+    while let Some(Reverse((_, cost, id))) = heap.pop() {
+        if cost > visited.best[id].0 {
+            continue;
+        }
+        if goal(&visited.states[id]) {
+            return Some(visited.path(id));
+        }
+        for (next, step) in neighbors(&visited.states[id]) {
+            let next_cost = cost + step;
+            let estimate = next_cost + heuristic(&next);
+            if let Some(next) = visited.relax(next, next_cost, id) {
+                heap.push(Reverse((estimate, next_cost, next)));
+            }
+        }
+    }
+    None
+}
+
+/// Dijkstra with a bucket queue, faster when edge costs are small integers.
+///
+/// Keeps one bucket per distinct cost up to the answer, so total costs must stay small too.
+pub fn dial<S, I>(
+    start: S,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let mut buckets = vec![vec![0]];
+    let mut visited = Visited::new(start, 0);
+    let mut pending = 1;
+
+    let mut cost = 0;
+    while pending > 0 {
+        while let Some(id) = buckets[cost].pop() {
+            pending -= 1;
+            if visited.best[id].0 < cost {
+                continue;
+            }
+            if goal(&visited.states[id]) {
+                return Some(visited.path(id));
+            }
+            for (next, step) in neighbors(&visited.states[id]) {
+                let next_cost = cost + step;
+                if let Some(next) = visited.relax(next, next_cost, id) {
+                    if buckets.len() <= next_cost {
+                        buckets.resize(next_cost + 1, vec![]);
+                    }
+                    buckets[next_cost].push(next);
+                    pending += 1;
+                }
+            }
+        }
+        cost += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Weighted digraph: 0 -1-> 1 -1-> 3, 0 -4-> 3, 1 -5-> 2, 3 -1-> 2, plus 4 on its own.
+    fn neighbors(&node: &u32) -> Vec<(u32, usize)> {
+        match node {
+            0 => vec![(1, 1), (3, 4)],
+            1 => vec![(2, 5), (3, 1)],
+            3 => vec![(2, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let expected = Some(Path {
+            cost: 3,
+            states: vec![0, 1, 3, 2],
+        });
+        assert_eq!(dijkstra(0, neighbors, |&n| n == 2), expected);
+        assert_eq!(dial(0, neighbors, |&n| n == 2), expected);
+        assert_eq!(
+            astar(0, neighbors, |&n| (n != 2) as usize, |&n| n == 2),
+            expected
+        );
+
+        let trivial = Some(Path {
+            cost: 0,
+            states: vec![1],
+        });
+        assert_eq!(dijkstra(1, neighbors, |&n| n == 1), trivial);
+        assert_eq!(dial(0, neighbors, |&n| n == 4), None);
+        assert_eq!(dijkstra(0, neighbors, |&n| n == 4), None);
+    }
+
+    #[test]
+    fn grid_astar() {
+        // 5x5 open grid with a wall on x = 2 except at y = 4
+        let wall = |(x, y): (i32, i32)| x == 2 && y != 4;
+        let neighbors = |&(x, y): &(i32, i32)| {
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .into_iter()
+                .map(move |(dx, dy)| (x + dx, y + dy))
+                .filter(|&(x, y)| (0..5).contains(&x) && (0..5).contains(&y) && !wall((x, y)))
+                .map(|p| (p, 1))
+                .collect::<Vec<_>>()
+        };
+        let manhattan = |&(x, y): &(i32, i32)| (4 - x).abs() + y.abs();
+        let path = astar((0, 0), neighbors, manhattan, |&p| p == (4, 0)).unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(path.states.len(), 13);
+        assert!(path.states.iter().all(|&p| !wall(p)));
+        assert_eq!(
+            dijkstra((0, 0), neighbors, |&p| p == (4, 0)).unwrap().cost,
+            12
+        );
+    }
+}
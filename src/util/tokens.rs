@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+/// Tokens to look for, each with the digit it stands for.
+pub type Table = &'static [(&'static str, u32)];
+
+pub const DIGITS: Table = &[
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+pub const ENGLISH: Table = &[
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+pub const ITALIAN: Table = &[
+    ("uno", 1),
+    ("due", 2),
+    ("tre", 3),
+    ("quattro", 4),
+    ("cinque", 5),
+    ("sei", 6),
+    ("sette", 7),
+    ("otto", 8),
+    ("nove", 9),
+];
+
+const ROOT: usize = 0;
+
+/// Aho-Corasick automaton over bytes, finding every token of its tables, overlapping ones
+/// included (`eightwo` is both `eight` and `two`).
+#[derive(Clone, Debug)]
+pub struct Parser {
+    nodes: Vec<Node>,
+}
+
+/// Where the automaton stands after some input, so that it can be fed one byte at a time,
+/// e.g. across the chunks of a stream.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct State(usize);
+
+#[derive(Clone, Debug)]
+struct Node {
+    /// Complete transitions: failures are already resolved.
+    next: [u32; 256],
+    /// Value and length of the longest token ending here.
+    output: Option<(u32, usize)>,
+}
+
+impl Parser {
+    pub fn new(tables: impl IntoIterator<Item = Table>) -> Self {
+        Self::build(tables, false)
+    }
+
+    /// Matches ASCII letters regardless of case.
+    pub fn ignoring_case(tables: impl IntoIterator<Item = Table>) -> Self {
+        Self::build(tables, true)
+    }
+
+    /// Scans `st` up to the first token, returning the value along with how many bytes to skip
+    /// before the next scan. Only the start of the token is skipped so that any overlapping
+    /// one is found next time; if a token is a prefix of another, the longer one is never found.
+    pub fn parse_complete(&self, st: &[u8]) -> (usize, Option<u32>) {
+        let mut state = ROOT;
+        for (i, &c) in st.iter().enumerate() {
+            state = self.step(state, c);
+            if let Some((n, len)) = self.nodes[state].output {
+                return (i + 2 - len, Some(n));
+            }
+        }
+        (st.len(), None)
+    }
+
+    /// Advances over `c`, returning the value of the longest token ending with it.
+    pub fn feed(&self, state: &mut State, c: u8) -> Option<u32> {
+        state.0 = self.step(state.0, c);
+        self.nodes[state.0].output.map(|(n, _)| n)
+    }
+
+    fn step(&self, state: usize, c: u8) -> usize {
+        self.nodes[state].next[c as usize] as usize
+    }
+
+    fn build(tables: impl IntoIterator<Item = Table>, fold_case: bool) -> Self {
+        let node = Node {
+            next: [ROOT as u32; 256],
+            output: None,
+        };
+        let fold = |c: u8| if fold_case { c.to_ascii_lowercase() } else { c };
+
+        // Trie of the tokens first, where the root doubles as "no child"
+        let mut nodes = vec![node.clone()];
+        for &(token, n) in tables.into_iter().flatten() {
+            assert!(!token.is_empty(), "empty token for {n}");
+            let mut cur = ROOT;
+            for c in token.bytes().map(fold) {
+                if nodes[cur].next[c as usize] as usize == ROOT {
+                    nodes[cur].next[c as usize] = nodes.len() as u32;
+                    nodes.push(node.clone());
+                }
+                cur = nodes[cur].next[c as usize] as usize;
+            }
+            // The first table to define a token wins
+            nodes[cur].output.get_or_insert((n, token.len()));
+        }
+
+        // Then the failure links, breadth-first so that shallower nodes are complete already.
+        // Missing transitions are replaced by the ones of the failure node, and outputs are
+        // inherited from it when no token ends right here.
+        let mut fail = vec![ROOT; nodes.len()];
+        let mut queue = VecDeque::new();
+        queue.push_back(ROOT);
+        while let Some(cur) = queue.pop_front() {
+            for c in 0..256 {
+                let child = nodes[cur].next[c] as usize;
+                if child == ROOT {
+                    nodes[cur].next[c] = nodes[fail[cur]].next[c];
+                } else {
+                    if cur != ROOT {
+                        fail[child] = nodes[fail[cur]].next[c] as usize;
+                    }
+                    let inherited = nodes[fail[child]].output;
+                    nodes[child].output = nodes[child].output.or(inherited);
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        if fold_case {
+            for node in &mut nodes {
+                for c in b'A'..=b'Z' {
+                    node.next[c as usize] = node.next[c.to_ascii_lowercase() as usize];
+                }
+            }
+        }
+        Self { nodes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(parser: &Parser, line: &str) -> Vec<u32> {
+        let line = line.as_bytes();
+        let mut res = vec![];
+        let mut i = 0;
+        while i < line.len() {
+            let (end, n) = parser.parse_complete(&line[i..]);
+            res.extend(n);
+            i += end;
+        }
+        res
+    }
+
+    #[test]
+    fn english() {
+        let parser = Parser::new([DIGITS, ENGLISH]);
+        assert_eq!(digits(&parser, "eightwothree"), [8, 2, 3]);
+        assert_eq!(digits(&parser, "xtwone3four"), [2, 1, 3, 4]);
+        assert_eq!(digits(&parser, "sevenine0oneight"), [7, 9, 0, 1, 8]);
+        assert_eq!(digits(&parser, "ninine"), [9]);
+        assert_eq!(digits(&parser, "EightWo"), []);
+    }
+
+    #[test]
+    fn other_tables() {
+        let parser = Parser::new([DIGITS, ITALIAN]);
+        assert_eq!(digits(&parser, "settenovex5quattrotto"), [7, 9, 5, 4, 8]);
+        assert_eq!(digits(&parser, "uncinqueduetre"), [5, 2, 3]);
+        assert_eq!(digits(&parser, "eightwo"), []);
+
+        let parser = Parser::ignoring_case([ENGLISH, ITALIAN]);
+        assert_eq!(digits(&parser, "EightWo"), [8, 2]);
+        assert_eq!(digits(&parser, "SeTTeNINE"), [7, 9]);
+        assert_eq!(digits(&parser, "Due1"), [2]);
+    }
+
+    #[test]
+    fn feeding() {
+        let parser = Parser::new([DIGITS, ENGLISH]);
+        let mut state = State::default();
+        let mut found = vec![];
+        // Tokens carry over from one call to the next
+        for chunk in ["sevenin", "e0e", "ightw", "o"] {
+            found.extend(chunk.bytes().filter_map(|c| parser.feed(&mut state, c)));
+        }
+        assert_eq!(found, [7, 9, 0, 8, 2]);
+    }
+}